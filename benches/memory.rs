@@ -0,0 +1,62 @@
+//! Peak heap usage of canonicalizing large documents, measured with a counting allocator.
+//!
+//! Run with `cargo bench --bench memory`.
+
+use std::io;
+
+use serde_json::{json, Map, Value};
+
+use common::peak_usage;
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+fn record(index: usize) -> Value {
+    json!({
+        "id": index,
+        "name": format!("record \"{index}\""),
+        "tags": ["a", "b", "c"],
+        "attributes": {"created": "2019-02-10T11:23:06Z", "size": index * 7, "ok": true},
+    })
+}
+
+/// Records in an array at the top level, every record is written out when it is complete.
+fn record_array(count: usize) -> Value {
+    Value::Array((0..count).map(record).collect())
+}
+
+/// Records as properties of one object, the whole document is buffered for sorting.
+fn record_object(count: usize) -> Value {
+    Value::Object(
+        (0..count)
+            .map(|index| (format!("record{index}"), record(index)))
+            .collect::<Map<_, _>>(),
+    )
+}
+
+/// Objects nested `depth` levels deep, with a long string on every level.
+fn deep_object(depth: usize) -> Value {
+    (0..depth).fold(
+        json!(null),
+        |inner, level| json!({"level": level, "text": "x".repeat(10_000), "inner": inner}),
+    )
+}
+
+fn report(name: &str, document: &Value) {
+    let size = serde_json::to_vec(document).unwrap().len();
+    let peak = peak_usage(|| {
+        serde_json_canonicalizer::to_writer(document, &mut io::sink()).unwrap();
+    });
+    println!(
+        "{name:<24} document {:>8} KiB  peak heap {:>8} KiB  ({:.2}x)",
+        size / 1024,
+        peak / 1024,
+        peak as f64 / size as f64
+    );
+}
+
+fn main() {
+    report("record array", &record_array(100_000));
+    report("record object", &record_object(100_000));
+    report("deep object", &deep_object(100));
+}
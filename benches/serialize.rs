@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde_json::{json, Map, Value};
+
+use serde_json_canonicalizer::{to_vec, value_to_vec};
+
+/// An object with `width` properties, the keys of every fourth one need escaping.
+fn wide_object(width: usize) -> Value {
+    let map: Map<String, Value> = (0..width)
+        .map(|index| {
+            let key = match index % 4 {
+                0 => format!("key \"{index}\"\n"),
+                1 => format!("\u{20ac}{index}"),
+                _ => format!("property{index}"),
+            };
+            (key, json!(index))
+        })
+        .collect();
+    Value::Object(map)
+}
+
+fn wide_objects(c: &mut Criterion) {
+    let mut group = c.benchmark_group("wide object");
+    for width in [100, 10_000] {
+        let object = wide_object(width);
+        group.throughput(Throughput::Elements(width as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(width), &object, |b, object| {
+            b.iter(|| to_vec(black_box(object)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn integer_keys(c: &mut Criterion) {
+    let map: BTreeMap<u32, bool> = (0..10_000).map(|key| (key, key % 2 == 0)).collect();
+    c.bench_function("integer keys", |b| {
+        b.iter(|| to_vec(black_box(&map)).unwrap())
+    });
+}
+
+fn nested_objects(c: &mut Criterion) {
+    let records: Vec<Value> = (0..1_000)
+        .map(|index| {
+            json!({
+                "id": index,
+                "name": format!("record {index}"),
+                "tags": ["a", "b", "c"],
+                "attributes": {"created": "2019-02-10T11:23:06Z", "size": index * 7, "ok": true},
+            })
+        })
+        .collect();
+    c.bench_function("nested objects", |b| {
+        b.iter(|| to_vec(black_box(&records)).unwrap())
+    });
+}
+
+fn long_arrays(c: &mut Criterion) {
+    let numbers: Vec<f64> = (0..100_000).map(|index| index as f64 / 7.0).collect();
+    c.bench_function("long array of numbers", |b| {
+        b.iter(|| to_vec(black_box(&numbers)).unwrap())
+    });
+
+    let strings: Vec<String> = (0..100_000).map(|index| format!("item\t{index}")).collect();
+    c.bench_function("long array of strings", |b| {
+        b.iter(|| to_vec(black_box(&strings)).unwrap())
+    });
+}
+
+/// Objects and arrays nested `depth` levels deep, with a few scalars on every level.
+fn deep_value(depth: usize) -> Value {
+    (0..depth).fold(json!(null), |inner, level| {
+        if level % 2 == 0 {
+            json!({"level": level, "name": "nested", "flag": true, "inner": inner})
+        } else {
+            json!([level, "nested", inner, false])
+        }
+    })
+}
+
+fn deep_nesting(c: &mut Criterion) {
+    let mut group = c.benchmark_group("deep nesting");
+    for depth in [16, 100] {
+        let value = deep_value(depth);
+        group.bench_with_input(BenchmarkId::from_parameter(depth), &value, |b, value| {
+            b.iter(|| to_vec(black_box(value)).unwrap())
+        });
+    }
+    group.finish();
+}
+
+fn value_fast_path(c: &mut Criterion) {
+    let mut group = c.benchmark_group("value");
+    for (name, value) in [
+        ("wide object", wide_object(10_000)),
+        ("deep nesting", deep_value(100)),
+    ] {
+        group.bench_with_input(BenchmarkId::new("to_vec", name), &value, |b, value| {
+            b.iter(|| to_vec(black_box(value)).unwrap())
+        });
+        group.bench_with_input(
+            BenchmarkId::new("value_to_vec", name),
+            &value,
+            |b, value| b.iter(|| value_to_vec(black_box(value)).unwrap()),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    wide_objects,
+    integer_keys,
+    nested_objects,
+    long_arrays,
+    deep_nesting,
+    value_fast_path
+);
+criterion_main!(benches);
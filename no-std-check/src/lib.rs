@@ -0,0 +1,36 @@
+#![no_std]
+
+extern crate alloc;
+
+use alloc::{string::String, vec::Vec};
+
+use serde::Serialize;
+use serde_json_canonicalizer::{to_digest, to_string, value_to_vec, Canonicalizer, Error};
+use sha2::Sha256;
+
+#[derive(Serialize)]
+pub struct Reading {
+    pub sensor: &'static str,
+    pub value: f64,
+}
+
+pub fn canonical_string(reading: &Reading) -> Result<String, Error> {
+    to_string(reading)
+}
+
+pub fn canonical_hash(reading: &Reading) -> Result<[u8; 32], Error> {
+    to_digest::<Sha256>(reading).map(Into::into)
+}
+
+pub fn canonical_value(json: &str) -> Result<Vec<u8>, Error> {
+    value_to_vec(&serde_json::from_str(json)?)
+}
+
+pub fn canonical_batch(readings: &[Reading]) -> Result<Vec<u8>, Error> {
+    let mut canonicalizer = Canonicalizer::new();
+    let mut output = Vec::new();
+    for reading in readings {
+        canonicalizer.canonicalize_into(reading, &mut output)?;
+    }
+    Ok(output)
+}
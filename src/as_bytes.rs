@@ -0,0 +1,61 @@
+//! Serde `with` module storing a field as the base64url encoded JCS bytes of its value.
+//!
+//! Like [as_string](crate::as_string), for documents that carry the canonical form as opaque
+//! bytes, the way JWS and JWT payloads are. The encoding is base64url without padding.
+//!
+//! # Usage
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Message {
+//!     #[serde(with = "serde_json_canonicalizer::as_bytes")]
+//!     payload: serde_json::Value,
+//! }
+//!
+//! let message = Message { payload: serde_json::json!({"b": 2, "a": 1}) };
+//! let json = serde_json::to_string(&message).unwrap();
+//!
+//! // base64url of {"a":1,"b":2}
+//! assert_eq!(json, r#"{"payload":"eyJhIjoxLCJiIjoyfQ"}"#);
+//! assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message);
+//! ```
+
+use alloc::string::String;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{from_slice_strict, to_vec};
+
+/// Serializes the value as the base64url encoding of its JCS bytes.
+///
+/// # Errors
+///
+/// Fails if the value cannot be canonicalized, see [to_vec].
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    let json = to_vec(value).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_str(&URL_SAFE_NO_PAD.encode(json))
+}
+
+/// Deserializes the value from the base64url encoding of its JSON bytes.
+///
+/// # Errors
+///
+/// Fails if the input is not a base64url string without padding, or if the decoded bytes are not
+/// I-JSON that `T` can be deserialized from, see [from_slice_strict].
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: DeserializeOwned,
+    D: Deserializer<'de>,
+{
+    let encoded = String::deserialize(deserializer)?;
+    let json = URL_SAFE_NO_PAD
+        .decode(encoded)
+        .map_err(serde::de::Error::custom)?;
+    from_slice_strict(&json).map_err(serde::de::Error::custom)
+}
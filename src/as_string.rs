@@ -0,0 +1,57 @@
+//! Serde `with` module storing a field as the JCS string of its value.
+//!
+//! Any `Serialize` field becomes a JSON string inside of a document that is not canonical itself.
+//! Deserializing parses the string strictly as I-JSON, without requiring it to be canonical; use
+//! [Canonical](crate::Canonical) to verify the canonical form and keep the received bytes.
+//!
+//! # Usage
+//! ```
+//! use serde::{Deserialize, Serialize};
+//!
+//! #[derive(Serialize, Deserialize, PartialEq, Debug)]
+//! struct Message {
+//!     #[serde(with = "serde_json_canonicalizer::as_string")]
+//!     claims: serde_json::Value,
+//! }
+//!
+//! let message = Message { claims: serde_json::json!({"sub": "x", "iat": 1}) };
+//! let json = serde_json::to_string(&message).unwrap();
+//!
+//! assert_eq!(json, r#"{"claims":"{\"iat\":1,\"sub\":\"x\"}"}"#);
+//! assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message);
+//! ```
+
+use alloc::string::String;
+
+use serde::{de::DeserializeOwned, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{from_str_strict, to_string};
+
+/// Serializes the value as a string holding its JCS form.
+///
+/// # Errors
+///
+/// Fails if the value cannot be canonicalized, see [to_string].
+pub fn serialize<T, S>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: Serialize,
+    S: Serializer,
+{
+    let json = to_string(value).map_err(serde::ser::Error::custom)?;
+    serializer.serialize_str(&json)
+}
+
+/// Deserializes the value from a string holding its JSON form.
+///
+/// # Errors
+///
+/// Fails if the input is not a string, or if the string is not I-JSON that `T` can be
+/// deserialized from, see [from_str_strict].
+pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+where
+    T: DeserializeOwned,
+    D: Deserializer<'de>,
+{
+    let json = String::deserialize(deserializer)?;
+    from_str_strict(&json).map_err(serde::de::Error::custom)
+}
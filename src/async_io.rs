@@ -0,0 +1,111 @@
+//! The implementation shared by the [tokio](crate::tokio) and [futures](crate::futures) modules.
+//!
+//! The `AsyncRead` and `AsyncWrite` traits of both have the same extension methods, so the
+//! functions are expanded in each module from the `io` module of its crate.
+
+/// Expands `to_async_writer` and `pipe_async` for the `AsyncRead`, `AsyncReadExt`, `AsyncWrite`
+/// and `AsyncWriteExt` traits of the given `io` module, documented under the given module name.
+macro_rules! async_io {
+    ($module:literal, $($io:tt)+) => {
+        use $($io)+::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+        use serde::Serialize;
+
+        use crate::{stream::PushPipe, to_vec, Error};
+
+        /// Size of the chunks read from the reader, and of the output collected before it is
+        /// written.
+        const CHUNK: usize = 8 * 1024;
+
+        /// Serialize the given data structure as JCS into the asynchronous writer.
+        ///
+        /// Serialization itself is synchronous, so the canonical form is built in memory and then
+        /// written to the writer in chunks of 8 KiB without blocking. The writer is flushed at the
+        /// end.
+        ///
+        /// # Usage
+        /// ```
+        /// # #[tokio::main(flavor = "current_thread")]
+        /// # async fn main() {
+        #[doc = concat!("use serde_json_canonicalizer::", $module, "::to_async_writer;")]
+        ///
+        /// let mut body = Vec::new();
+        /// to_async_writer(&serde_json::json!({"b": 2, "a": 1}), &mut body).await.unwrap();
+        ///
+        /// assert_eq!(body, br#"{"a":1,"b":2}"#);
+        /// # }
+        /// ```
+        ///
+        /// # Errors
+        ///
+        /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+        /// fail, if `T` contains a map with non-string keys, or if writing fails.
+        pub async fn to_async_writer<S, W>(value: &S, writer: &mut W) -> Result<(), Error>
+        where
+            S: Serialize,
+            W: ?Sized + AsyncWrite + Unpin,
+        {
+            write_chunked(&to_vec(value)?, writer).await?;
+            writer.flush().await.map_err(Error::Io)
+        }
+
+        /// Pipe JSON text from an asynchronous reader into JCS written to an asynchronous writer.
+        ///
+        /// The elements of a top-level array are canonicalized and written out one by one as they
+        /// are read, any other value is collected until the input ends. The output is the same as
+        /// the one of [pipe](crate::pipe). The writer is flushed at the end.
+        ///
+        /// # Usage
+        /// ```
+        /// # #[tokio::main(flavor = "current_thread")]
+        /// # async fn main() {
+        #[doc = concat!("use serde_json_canonicalizer::", $module, "::pipe_async;")]
+        ///
+        /// let input = r#"[{"b": false, "a": 12e1}, "Hello!"]"#;
+        /// let mut output = Vec::new();
+        /// pipe_async(input.as_bytes(), &mut output).await.unwrap();
+        ///
+        /// assert_eq!(output, br#"[{"a":120,"b":false},"Hello!"]"#);
+        /// # }
+        /// ```
+        ///
+        /// # Errors
+        ///
+        /// Fails on malformed JSON, unicode lone surrogates and numbers out of range, or if
+        /// reading or writing fails. Whatever was written before the error stays in the writer.
+        pub async fn pipe_async<R, W>(mut reader: R, mut writer: W) -> Result<(), Error>
+        where
+            R: AsyncRead + Unpin,
+            W: AsyncWrite + Unpin,
+        {
+            let mut pipe = PushPipe::default();
+            let mut chunk = vec![0; CHUNK];
+            loop {
+                let read = reader.read(&mut chunk).await.map_err(Error::Io)?;
+                if read == 0 {
+                    break;
+                }
+                pipe.push(&chunk[..read])?;
+                if pipe.output().len() >= CHUNK {
+                    write_chunked(pipe.output(), &mut writer).await?;
+                    pipe.clear_output();
+                }
+            }
+            pipe.finish()?;
+            write_chunked(pipe.output(), &mut writer).await?;
+            writer.flush().await.map_err(Error::Io)
+        }
+
+        /// Writes the output in chunks, so that no single write holds the whole document.
+        async fn write_chunked<W>(output: &[u8], writer: &mut W) -> Result<(), Error>
+        where
+            W: ?Sized + AsyncWrite + Unpin,
+        {
+            for chunk in output.chunks(CHUNK) {
+                writer.write_all(chunk).await.map_err(Error::Io)?;
+            }
+            Ok(())
+        }
+    };
+}
+
+pub(crate) use async_io;
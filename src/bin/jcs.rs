@@ -0,0 +1,253 @@
+//! Command-line interface to canonicalize, check, hash and compare JSON files.
+//!
+//! Reads a file, or the standard input when the file is `-` or omitted. Exits with 1 when the
+//! input is not canonical or the documents differ, and with 2 on errors.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use clap::{error::ErrorKind, CommandFactory, Parser, Subcommand, ValueEnum};
+use serde_json::Value;
+use sha2::{Digest, Sha256, Sha512};
+
+use serde_json_canonicalizer::{pipe_reader, to_string, verify_canonical};
+
+#[path = "../hex.rs"]
+mod hex;
+
+#[derive(Parser)]
+#[command(
+    name = "jcs",
+    version,
+    about = "JSON Canonicalization Scheme (RFC 8785)"
+)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Write the canonical form of the input to the standard output.
+    Canonicalize {
+        /// Input file, the standard input if `-` or omitted.
+        file: Option<PathBuf>,
+    },
+    /// Check that the input is already canonical, byte for byte.
+    Check {
+        /// Input file, the standard input if `-` or omitted.
+        file: Option<PathBuf>,
+    },
+    /// Hash the canonical form of the input.
+    Hash {
+        /// Input file, the standard input if `-` or omitted.
+        file: Option<PathBuf>,
+        /// Hash function.
+        #[arg(short, long, value_enum, default_value_t = Algorithm::Sha256)]
+        algorithm: Algorithm,
+        /// Encoding of the hash.
+        #[arg(short, long, value_enum, default_value_t = Encoding::Hex)]
+        encoding: Encoding,
+    },
+    /// Compare two documents after canonicalization, listing the JSON Pointers that differ.
+    Diff {
+        /// First document, the standard input if `-`.
+        left: PathBuf,
+        /// Second document, the standard input if `-`.
+        right: PathBuf,
+    },
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Algorithm {
+    Sha256,
+    Sha512,
+    Blake3,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum Encoding {
+    Hex,
+    Base64url,
+}
+
+/// Error of a command, printed with the input it is about.
+struct Failure {
+    input: String,
+    error: Box<dyn std::error::Error>,
+}
+
+fn main() -> ExitCode {
+    let result = match Cli::parse().command {
+        Command::Canonicalize { file } => canonicalize(file.as_deref()),
+        Command::Check { file } => check(file.as_deref()),
+        Command::Hash {
+            file,
+            algorithm,
+            encoding,
+        } => hash(file.as_deref(), algorithm, encoding),
+        Command::Diff { left, right } => {
+            if is_stdin(&left) && is_stdin(&right) {
+                Cli::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "only one of the documents to diff can be read from the standard input",
+                    )
+                    .exit();
+            }
+            diff(&left, &right)
+        }
+    };
+    match result {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::from(1),
+        Err(Failure { input, error }) => {
+            eprintln!("jcs: {input}: {error}");
+            ExitCode::from(2)
+        }
+    }
+}
+
+fn canonicalize(file: Option<&Path>) -> Result<bool, Failure> {
+    let reader = open(file)?;
+    let mut writer = BufWriter::new(io::stdout().lock());
+    pipe_reader(reader, &mut writer).map_err(|error| failure(file, error))?;
+    writer.flush().map_err(|error| failure(file, error))?;
+    Ok(true)
+}
+
+fn check(file: Option<&Path>) -> Result<bool, Failure> {
+    let mut json = Vec::new();
+    open(file)?
+        .read_to_end(&mut json)
+        .map_err(|error| failure(file, error))?;
+    match verify_canonical(&json) {
+        Ok(()) => Ok(true),
+        Err(error) => {
+            println!("{}: not canonical: {error}", name(file));
+            Ok(false)
+        }
+    }
+}
+
+fn hash(file: Option<&Path>, algorithm: Algorithm, encoding: Encoding) -> Result<bool, Failure> {
+    let mut json = Vec::new();
+    pipe_reader(open(file)?, &mut json).map_err(|error| failure(file, error))?;
+    let hash = match algorithm {
+        Algorithm::Sha256 => Sha256::digest(&json).to_vec(),
+        Algorithm::Sha512 => Sha512::digest(&json).to_vec(),
+        Algorithm::Blake3 => blake3::hash(&json).as_bytes().to_vec(),
+    };
+    match encoding {
+        Encoding::Hex => println!("{}", hex::encode(&hash)),
+        Encoding::Base64url => println!("{}", URL_SAFE_NO_PAD.encode(hash)),
+    }
+    Ok(true)
+}
+
+fn diff(left: &Path, right: &Path) -> Result<bool, Failure> {
+    let left = read_value(left)?;
+    let right = read_value(right)?;
+    let mut differences = Vec::new();
+    compare(&mut String::new(), &left, &right, &mut differences);
+    for difference in &differences {
+        println!("{difference}");
+    }
+    Ok(differences.is_empty())
+}
+
+/// Collects the differences of two values, with `-` for removed, `+` for added and `~` for changed
+/// values, each with its JSON Pointer.
+fn compare(path: &mut String, left: &Value, right: &Value, differences: &mut Vec<String>) {
+    let depth = path.len();
+    match (left, right) {
+        (Value::Object(left), Value::Object(right)) => {
+            // in the order of the canonical form, by UTF-16 code units
+            let mut keys: Vec<_> = left.keys().chain(right.keys()).collect();
+            keys.sort_by(|a, b| a.encode_utf16().cmp(b.encode_utf16()));
+            keys.dedup();
+            for key in keys {
+                path.push('/');
+                path.push_str(&key.replace('~', "~0").replace('/', "~1"));
+                match (left.get(key), right.get(key)) {
+                    (Some(left), Some(right)) => compare(path, left, right, differences),
+                    (Some(left), None) => {
+                        differences.push(format!("- {path}: {}", canonical(left)))
+                    }
+                    (None, Some(right)) => {
+                        differences.push(format!("+ {path}: {}", canonical(right)))
+                    }
+                    (None, None) => unreachable!("the key is in one of the objects"),
+                }
+                path.truncate(depth);
+            }
+        }
+        (Value::Array(left), Value::Array(right)) => {
+            for index in 0..left.len().max(right.len()) {
+                path.push('/');
+                path.push_str(&index.to_string());
+                match (left.get(index), right.get(index)) {
+                    (Some(left), Some(right)) => compare(path, left, right, differences),
+                    (Some(left), None) => {
+                        differences.push(format!("- {path}: {}", canonical(left)))
+                    }
+                    (None, Some(right)) => {
+                        differences.push(format!("+ {path}: {}", canonical(right)))
+                    }
+                    (None, None) => unreachable!("the index is in one of the arrays"),
+                }
+                path.truncate(depth);
+            }
+        }
+        (left, right) => {
+            let (left, right) = (canonical(left), canonical(right));
+            if left != right {
+                differences.push(format!("~ {path}: {left} -> {right}"));
+            }
+        }
+    }
+}
+
+/// The canonical form of a parsed value, which cannot fail as it holds only finite numbers.
+fn canonical(value: &Value) -> String {
+    to_string(value).expect("parsed JSON canonicalizes")
+}
+
+fn read_value(file: &Path) -> Result<Value, Failure> {
+    let file = Some(file);
+    serde_json::from_reader(open(file)?).map_err(|error| failure(file, error))
+}
+
+fn open(file: Option<&Path>) -> Result<Box<dyn Read>, Failure> {
+    match file {
+        None => Ok(Box::new(io::stdin().lock())),
+        Some(path) if is_stdin(path) => Ok(Box::new(io::stdin().lock())),
+        Some(path) => match File::open(path) {
+            Ok(file) => Ok(Box::new(BufReader::new(file))),
+            Err(error) => Err(failure(Some(path), error)),
+        },
+    }
+}
+
+fn is_stdin(path: &Path) -> bool {
+    path == Path::new("-")
+}
+
+fn name(file: Option<&Path>) -> String {
+    match file {
+        Some(path) if !is_stdin(path) => path.display().to_string(),
+        _ => "<stdin>".to_string(),
+    }
+}
+
+fn failure(file: Option<&Path>, error: impl Into<Box<dyn std::error::Error>>) -> Failure {
+    Failure {
+        input: name(file),
+        error: error.into(),
+    }
+}
@@ -0,0 +1,259 @@
+//! Values along with their canonical form.
+
+use alloc::{format, string::String};
+use core::{fmt, ops::Deref, str::FromStr};
+
+use serde::{
+    de::{DeserializeOwned, Error as DeError},
+    ser::Error as SerError,
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+use serde_json::Value;
+
+use crate::{
+    strict::{deserialize_strict, strict_value},
+    to_string,
+    value::value_to_vec,
+    verify::verify_canonical,
+    Error,
+};
+
+/// A value together with the exact bytes of its JCS canonical form.
+///
+/// Serializes as a JSON string holding the canonical form of the value, with any serializer, so
+/// a canonical payload can be embedded into a larger message and signed on its own. Deserializing
+/// verifies that the string is byte-exact JCS before parsing the value from it, and keeps the
+/// string, so a signature can be checked against the bytes that were received.
+///
+/// The value cannot be changed, so that it never goes out of sync with its canonical form.
+///
+/// # Usage
+/// ```
+/// use serde::{Deserialize, Serialize};
+/// use serde_json_canonicalizer::Canonical;
+///
+/// #[derive(Serialize, Deserialize)]
+/// struct Envelope {
+///     payload: Canonical<serde_json::Value>,
+///     signature: String,
+/// }
+///
+/// let payload = Canonical::new(serde_json::json!({"b": 2, "a": 1})).unwrap();
+/// assert_eq!(payload.as_str(), r#"{"a":1,"b":2}"#);
+///
+/// let envelope = Envelope { payload, signature: "…".to_string() };
+/// let json = serde_json::to_string(&envelope).unwrap();
+/// assert_eq!(json, r#"{"payload":"{\"a\":1,\"b\":2}","signature":"…"}"#);
+///
+/// let received: Envelope = serde_json::from_str(&json).unwrap();
+/// assert_eq!(received.payload.as_bytes(), br#"{"a":1,"b":2}"#);
+/// assert_eq!(received.payload["a"], 1);
+///
+/// let tampered = r#"{"payload":"{\"b\":2,\"a\":1}","signature":"…"}"#;
+/// assert!(serde_json::from_str::<Envelope>(tampered).is_err());
+/// ```
+#[derive(Clone, Debug)]
+pub struct Canonical<T> {
+    value: T,
+    json: String,
+}
+
+impl<T: Serialize> Canonical<T> {
+    /// Serializes the value as JCS and keeps it along with the canonical form.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail, or if `T` contains a map with non-string keys.
+    pub fn new(value: T) -> Result<Self, Error> {
+        let json = to_string(&value)?;
+        Ok(Self { value, json })
+    }
+}
+
+impl<T> Canonical<T> {
+    /// The value.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    /// The canonical form of the value.
+    pub fn as_str(&self) -> &str {
+        &self.json
+    }
+
+    /// The bytes of the canonical form of the value.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.json.as_bytes()
+    }
+
+    /// Consumes the wrapper returning the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+
+    /// Consumes the wrapper returning the value and its canonical form.
+    pub fn into_parts(self) -> (T, String) {
+        (self.value, self.json)
+    }
+}
+
+impl<T> Deref for Canonical<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.value
+    }
+}
+
+/// Serializes the canonical form as a string.
+impl<T> Serialize for Canonical<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.json)
+    }
+}
+
+/// Deserializes a string that holds the canonical form of a value.
+impl<'de, T: DeserializeOwned> Deserialize<'de> for Canonical<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let json = String::deserialize(deserializer)?;
+        verify_canonical(json.as_bytes())
+            .map_err(|error| D::Error::custom(format!("JSON is not canonical: {error}")))?;
+        let value = serde_json::from_str(&json).map_err(D::Error::custom)?;
+        Ok(Self { value, json })
+    }
+}
+
+/// A JSON value held in its canonical form, compared and hashed by its canonical bytes.
+///
+/// Values that JCS serializes the same way are equal, such as `1.0` and `1`, or objects with the
+/// same properties in a different order, so a `CanonicalValue` can key a `HashMap` or a `BTreeMap`
+/// to deduplicate JSON documents. [Value] implements neither `Hash` nor `Ord`, and its `Eq` tells
+/// these apart.
+///
+/// The order is the byte order of the canonical forms, which is not the numeric order of numbers.
+///
+/// # Usage
+/// ```
+/// use std::collections::HashSet;
+///
+/// use serde_json::json;
+/// use serde_json_canonicalizer::CanonicalValue;
+///
+/// let documents = [json!({"a": 1.0, "b": [true]}), json!({"b": [true], "a": 1})];
+/// let unique: HashSet<CanonicalValue> = documents
+///     .iter()
+///     .map(|document| CanonicalValue::new(document).unwrap())
+///     .collect();
+///
+/// assert_eq!(unique.len(), 1);
+/// let value = unique.into_iter().next().unwrap();
+/// assert_eq!(value.as_str(), r#"{"a":1,"b":[true]}"#);
+/// assert_eq!(value.to_value().unwrap(), json!({"a": 1, "b": [true]}));
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct CanonicalValue {
+    json: String,
+}
+
+impl CanonicalValue {
+    /// Serializes the value as JCS.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can only fail for numbers that are not finite doubles, which a [Value] holds
+    /// only with the `arbitrary_precision` feature of [serde_json].
+    pub fn new(value: &Value) -> Result<Self, Error> {
+        let json = value_to_vec(value)?;
+        // the serializer only writes valid UTF-8
+        let json = String::from_utf8(json).expect("JCS output is UTF-8");
+        Ok(Self { json })
+    }
+
+    /// The canonical form of the value.
+    pub fn as_str(&self) -> &str {
+        &self.json
+    }
+
+    /// The bytes of the canonical form of the value.
+    pub fn as_bytes(&self) -> &[u8] {
+        self.json.as_bytes()
+    }
+
+    /// Consumes the value returning its canonical form.
+    pub fn into_string(self) -> String {
+        self.json
+    }
+
+    /// Parses the canonical form back into a [Value].
+    ///
+    /// # Errors
+    ///
+    /// Fails only for values nested deeper than the recursion limit of [serde_json] allows it to
+    /// parse, which a [Value] that was parsed by it never is.
+    pub fn to_value(&self) -> Result<Value, Error> {
+        Ok(serde_json::from_str(&self.json)?)
+    }
+}
+
+impl TryFrom<&Value> for CanonicalValue {
+    type Error = Error;
+
+    fn try_from(value: &Value) -> Result<Self, Error> {
+        Self::new(value)
+    }
+}
+
+impl TryFrom<Value> for CanonicalValue {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Error> {
+        Self::new(&value)
+    }
+}
+
+impl TryFrom<CanonicalValue> for Value {
+    type Error = Error;
+
+    fn try_from(value: CanonicalValue) -> Result<Self, Error> {
+        value.to_value()
+    }
+}
+
+/// Parses JSON text strictly as I-JSON.
+impl FromStr for CanonicalValue {
+    type Err = Error;
+
+    fn from_str(json: &str) -> Result<Self, Error> {
+        Self::new(&strict_value(json.as_bytes())?)
+    }
+}
+
+impl fmt::Display for CanonicalValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.json)
+    }
+}
+
+impl fmt::Debug for CanonicalValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("CanonicalValue").field(&self.json).finish()
+    }
+}
+
+/// Serializes the value itself, not its canonical form as a string.
+impl Serialize for CanonicalValue {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.to_value()
+            .map_err(S::Error::custom)?
+            .serialize(serializer)
+    }
+}
+
+/// Deserializes the value strictly like [FromStr], failing on duplicate object keys instead of
+/// keeping the last one as deserializing a [Value] does.
+impl<'de> Deserialize<'de> for CanonicalValue {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::new(&deserialize_strict(deserializer)?).map_err(D::Error::custom)
+    }
+}
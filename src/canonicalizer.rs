@@ -0,0 +1,98 @@
+//! Canonicalizing many values while reusing the buffers of the serializer.
+
+use alloc::vec::Vec;
+use core::mem;
+
+use serde::Serialize;
+
+use crate::{
+    jcs::{IntegerPolicy, JcsFormatter, JcsSerializer},
+    Error,
+};
+
+/// A reusable JCS serializer that keeps its buffers across calls.
+///
+/// [to_vec](crate::to_vec) sets up a new serializer for every value, which allocates the buffers
+/// for sorting object properties again each time. A `Canonicalizer` keeps them, so once it has
+/// seen values of a similar size, canonicalizing more of them does not allocate.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::Canonicalizer;
+///
+/// let mut canonicalizer = Canonicalizer::new();
+/// let mut output = Vec::new();
+/// for id in 0..3 {
+///     output.clear();
+///     canonicalizer
+///         .canonicalize_into(&serde_json::json!({"id": id, "name": "x"}), &mut output)
+///         .unwrap();
+///     assert_eq!(output, format!(r#"{{"id":{id},"name":"x"}}"#).as_bytes());
+/// }
+///
+/// assert_eq!(canonicalizer.canonicalize(&[2, 1]).unwrap(), b"[2,1]");
+/// ```
+#[derive(Default)]
+pub struct Canonicalizer {
+    formatter: JcsFormatter,
+    integer_policy: IntegerPolicy,
+    output: Vec<u8>,
+}
+
+impl Canonicalizer {
+    /// Creates a canonicalizer with empty buffers.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets how integers that cannot be represented exactly as a double are serialized.
+    pub fn with_integer_policy(mut self, integer_policy: IntegerPolicy) -> Self {
+        self.integer_policy = integer_policy;
+        self
+    }
+
+    /// Serialize the given data structure as JCS, appending it to `output`.
+    ///
+    /// If the serialization fails, `output` is left as it was.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail, or if `T` contains a map with non-string keys.
+    pub fn canonicalize_into<T>(&mut self, value: &T, output: &mut Vec<u8>) -> Result<(), Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let len = output.len();
+        let formatter = mem::take(&mut self.formatter);
+        let mut serializer =
+            JcsSerializer::from_parts(&mut *output, formatter, self.integer_policy);
+        let result = value.serialize(&mut serializer);
+        let (_, mut formatter) = serializer.into_parts();
+        if result.is_err() {
+            formatter.reset();
+            output.truncate(len);
+        }
+        self.formatter = formatter;
+        result
+    }
+
+    /// Serialize the given data structure as JCS into the output buffer of the canonicalizer.
+    ///
+    /// The returned bytes are valid until the next call.
+    ///
+    /// # Errors
+    ///
+    /// Serialization can fail if `T`'s implementation of `Serialize` decides to
+    /// fail, or if `T` contains a map with non-string keys.
+    pub fn canonicalize<T>(&mut self, value: &T) -> Result<&[u8], Error>
+    where
+        T: ?Sized + Serialize,
+    {
+        let mut output = mem::take(&mut self.output);
+        output.clear();
+        let result = self.canonicalize_into(value, &mut output);
+        self.output = output;
+        result.map(|()| self.output.as_slice())
+    }
+}
@@ -0,0 +1,259 @@
+//! W3C Data Integrity proofs with the JCS based cryptosuites.
+//!
+//! Implements `eddsa-jcs-2022` with Ed25519 keys and `ecdsa-jcs-2019` with P-256 keys. Both
+//! canonicalize the proof configuration and the unsecured document with JCS, hash them separately
+//! with SHA-256, and sign the concatenation of the two hashes.
+//!
+//! Resolving the `verificationMethod` of a proof into a key is left to the application.
+
+use std::fmt::Display;
+
+use ed25519_dalek::{Signer as _, Verifier as _};
+use serde_json::{Map, Value};
+use sha2::Sha256;
+
+use crate::{json_error::json_error, to_digest};
+
+const PROOF_TYPE: &str = "DataIntegrityProof";
+const EDDSA_JCS_2022: &str = "eddsa-jcs-2022";
+const ECDSA_JCS_2019: &str = "ecdsa-jcs-2019";
+
+/// Error creating or verifying a Data Integrity proof.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The document could not be canonicalized.
+    Json(crate::Error),
+    /// The document, or its proof, is not a JSON object.
+    NotAnObject,
+    /// The proof options are not valid for the cryptosuite, the message describes why.
+    InvalidProof(&'static str),
+    /// The cryptosuite of the proof does not match the type of the key.
+    CryptosuiteMismatch,
+    /// The `proofValue` is not a base58btc multibase encoded signature.
+    InvalidProofValue,
+    /// The signature does not match the document.
+    VerificationFailed,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Json(error) => write!(f, "{error}"),
+            Error::NotAnObject => f.write_str("document or proof is not a JSON object"),
+            Error::InvalidProof(reason) => write!(f, "invalid proof: {reason}"),
+            Error::CryptosuiteMismatch => f.write_str("cryptosuite does not match the key type"),
+            Error::InvalidProofValue => f.write_str("proofValue is not a valid signature"),
+            Error::VerificationFailed => f.write_str("proof verification failed"),
+        }
+    }
+}
+
+json_error!(Error);
+
+/// A private key creating proofs.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum SigningKey {
+    /// Ed25519 key for the `eddsa-jcs-2022` cryptosuite.
+    Ed25519(ed25519_dalek::SigningKey),
+    /// P-256 key for the `ecdsa-jcs-2019` cryptosuite.
+    P256(p256::ecdsa::SigningKey),
+}
+
+impl SigningKey {
+    fn cryptosuite(&self) -> &'static str {
+        match self {
+            SigningKey::Ed25519(_) => EDDSA_JCS_2022,
+            SigningKey::P256(_) => ECDSA_JCS_2019,
+        }
+    }
+
+    fn sign(&self, hash_data: &[u8]) -> Vec<u8> {
+        match self {
+            SigningKey::Ed25519(key) => key.sign(hash_data).to_vec(),
+            SigningKey::P256(key) => {
+                let signature: p256::ecdsa::Signature = key.sign(hash_data);
+                signature.to_vec()
+            }
+        }
+    }
+}
+
+/// A public key verifying proofs.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum VerifyingKey {
+    /// Ed25519 key for the `eddsa-jcs-2022` cryptosuite.
+    Ed25519(ed25519_dalek::VerifyingKey),
+    /// P-256 key for the `ecdsa-jcs-2019` cryptosuite.
+    P256(p256::ecdsa::VerifyingKey),
+}
+
+impl VerifyingKey {
+    fn cryptosuite(&self) -> &'static str {
+        match self {
+            VerifyingKey::Ed25519(_) => EDDSA_JCS_2022,
+            VerifyingKey::P256(_) => ECDSA_JCS_2019,
+        }
+    }
+
+    fn verify(&self, hash_data: &[u8], proof_bytes: &[u8]) -> Result<(), Error> {
+        let verified = match self {
+            VerifyingKey::Ed25519(key) => ed25519_dalek::Signature::from_slice(proof_bytes)
+                .and_then(|signature| key.verify(hash_data, &signature)),
+            VerifyingKey::P256(key) => p256::ecdsa::Signature::from_slice(proof_bytes)
+                .and_then(|signature| key.verify(hash_data, &signature)),
+        };
+        verified.map_err(|_| Error::VerificationFailed)
+    }
+}
+
+impl From<&SigningKey> for VerifyingKey {
+    fn from(key: &SigningKey) -> Self {
+        match key {
+            SigningKey::Ed25519(key) => VerifyingKey::Ed25519(key.verifying_key()),
+            SigningKey::P256(key) => VerifyingKey::P256(*key.verifying_key()),
+        }
+    }
+}
+
+/// Checks the proof options and returns the proof configuration, which is the proof without its
+/// `proofValue`.
+fn proof_configuration(options: &Map<String, Value>) -> Result<Map<String, Value>, Error> {
+    if options.get("type").and_then(Value::as_str) != Some(PROOF_TYPE) {
+        return Err(Error::InvalidProof("type is not DataIntegrityProof"));
+    }
+    match options.get("cryptosuite").and_then(Value::as_str) {
+        Some(EDDSA_JCS_2022 | ECDSA_JCS_2019) => {}
+        _ => return Err(Error::InvalidProof("unsupported cryptosuite")),
+    }
+    let mut configuration = options.clone();
+    configuration.remove("proofValue");
+    Ok(configuration)
+}
+
+/// Returns the document without its proof.
+fn unsecured(document: &Value) -> Result<Map<String, Value>, Error> {
+    let mut unsecured = document.as_object().ok_or(Error::NotAnObject)?.clone();
+    unsecured.remove("proof");
+    Ok(unsecured)
+}
+
+/// Compute the data that is signed for the proof options and the document.
+///
+/// This is the transformation, proof configuration and hashing steps of the cryptosuites: the
+/// SHA-256 hash of the JCS form of the proof configuration followed by the SHA-256 hash of the
+/// JCS form of the unsecured document. The `proofValue` of the options and the `proof` of the
+/// document are ignored.
+///
+/// # Errors
+///
+/// Fails if the document or options are not objects, or the options are not for one of the
+/// supported cryptosuites.
+pub fn hash_data(document: &Value, proof_options: &Value) -> Result<Vec<u8>, Error> {
+    let configuration = proof_configuration(proof_options.as_object().ok_or(Error::NotAnObject)?)?;
+    let unsecured = unsecured(document)?;
+    let mut hash_data = to_digest::<Sha256>(&configuration)?.to_vec();
+    hash_data.extend(to_digest::<Sha256>(&unsecured)?);
+    Ok(hash_data)
+}
+
+/// Create a proof for the document with the given proof options.
+///
+/// The `type` and `cryptosuite` of the options are filled in from the key if missing, and the
+/// `@context` of the document is copied into the proof. The returned proof is meant to be added to
+/// the document as its `proof` property.
+///
+/// # Usage
+/// ```
+/// use serde_json::json;
+/// use serde_json_canonicalizer::data_integrity::{create_proof, verify_proof, SigningKey};
+///
+/// let key = SigningKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(&[7; 32]));
+/// let mut document = json!({"@context": ["https://www.w3.org/ns/credentials/v2"], "name": "x"});
+/// let options = json!({
+///     "created": "2023-02-24T23:36:38Z",
+///     "verificationMethod": "https://example.com/issuer#key-1",
+///     "proofPurpose": "assertionMethod",
+/// });
+/// document["proof"] = create_proof(&document, &options, &key).unwrap();
+///
+/// assert_eq!(document["proof"]["cryptosuite"], "eddsa-jcs-2022");
+/// verify_proof(&document, &(&key).into()).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Fails if the document or options are not objects, or the options specify a different type or
+/// cryptosuite than the one of the key.
+pub fn create_proof(document: &Value, options: &Value, key: &SigningKey) -> Result<Value, Error> {
+    let mut proof = options.as_object().ok_or(Error::NotAnObject)?.clone();
+    proof.entry("type").or_insert_with(|| PROOF_TYPE.into());
+    let cryptosuite = proof
+        .entry("cryptosuite")
+        .or_insert_with(|| key.cryptosuite().into());
+    if cryptosuite != key.cryptosuite() {
+        return Err(Error::CryptosuiteMismatch);
+    }
+    if let Some(context) = document.get("@context") {
+        proof.insert("@context".to_owned(), context.clone());
+    }
+    proof.remove("proofValue");
+
+    let proof_bytes = key.sign(&hash_data(document, &Value::Object(proof.clone()))?);
+    let proof_value = format!("z{}", bs58::encode(proof_bytes).into_string());
+    proof.insert("proofValue".to_owned(), proof_value.into());
+    Ok(Value::Object(proof))
+}
+
+/// Checks that the `@context` of the document starts with the `@context` of the proof.
+fn context_matches(document_context: Option<&Value>, proof_context: &Value) -> bool {
+    let as_slice = |context: &'_ Value| -> Vec<Value> {
+        match context {
+            Value::Array(contexts) => contexts.clone(),
+            context => vec![context.clone()],
+        }
+    };
+    let proof_context = as_slice(proof_context);
+    document_context
+        .map(as_slice)
+        .is_some_and(|document_context| document_context.starts_with(&proof_context))
+}
+
+/// Verify the `proof` of a secured document.
+///
+/// # Errors
+///
+/// Fails with [Error::VerificationFailed] if the signature does not match, or with another error
+/// if the document or the proof is malformed or its cryptosuite does not match the key.
+pub fn verify_proof(document: &Value, key: &VerifyingKey) -> Result<(), Error> {
+    let proof = document
+        .get("proof")
+        .ok_or(Error::InvalidProof("document has no proof"))?
+        .as_object()
+        .ok_or(Error::NotAnObject)?;
+    let configuration = proof_configuration(proof)?;
+    if configuration.get("cryptosuite").and_then(Value::as_str) != Some(key.cryptosuite()) {
+        return Err(Error::CryptosuiteMismatch);
+    }
+    let proof_bytes = proof
+        .get("proofValue")
+        .and_then(Value::as_str)
+        .and_then(|proof_value| proof_value.strip_prefix('z'))
+        .and_then(|encoded| bs58::decode(encoded).into_vec().ok())
+        .ok_or(Error::InvalidProofValue)?;
+
+    let mut unsecured = unsecured(document)?;
+    if let Some(context) = configuration.get("@context") {
+        if !context_matches(unsecured.get("@context"), context) {
+            return Err(Error::InvalidProof(
+                "document context does not match the proof",
+            ));
+        }
+        unsecured.insert("@context".to_owned(), context.clone());
+    }
+
+    let hash_data = hash_data(&Value::Object(unsecured), &Value::Object(configuration))?;
+    key.verify(&hash_data, &proof_bytes)
+}
@@ -0,0 +1,206 @@
+//! The error of canonicalization, pointing to the value that could not be serialized.
+
+use alloc::string::{String, ToString};
+use core::fmt;
+
+use serde::ser::StdError;
+
+use crate::io;
+
+/// Error of canonicalizing a value as JCS.
+///
+/// The errors about a value carry its JSON Pointer (RFC 6901) in the serialized document, like
+/// `/orders/3/price`, which is empty for the document itself.
+///
+/// # Usage
+/// ```
+/// use std::collections::BTreeMap;
+///
+/// use serde::Serialize;
+/// use serde_json_canonicalizer::{to_vec, Error};
+///
+/// #[derive(Serialize)]
+/// struct Order {
+///     price: f64,
+/// }
+///
+/// let orders = [Order { price: 1.5 }, Order { price: f64::NAN }];
+/// let error = to_vec(&BTreeMap::from([("orders", orders)])).unwrap_err();
+///
+/// assert!(matches!(error, Error::NonFiniteNumber { .. }));
+/// assert_eq!(error.path(), Some("/orders/1/price"));
+/// assert_eq!(
+///     error.to_string(),
+///     "NaN and +/-Infinity are not permitted in JSON at `/orders/1/price`"
+/// );
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// A float is NaN or infinite, which JSON cannot represent.
+    NonFiniteNumber {
+        /// JSON Pointer of the number, or of the object for a map key.
+        path: String,
+    },
+    /// A map key is not a string, a number, a boolean or a unit variant.
+    NonStringKey {
+        /// JSON Pointer of the object.
+        path: String,
+    },
+    /// An integer is outside of the range that doubles represent exactly, with
+    /// [IntegerPolicy::Error](crate::IntegerPolicy::Error).
+    LossyInteger {
+        /// JSON Pointer of the integer, or of the object for a map key.
+        path: String,
+        /// The decimal digits of the integer.
+        value: String,
+    },
+    /// A struct with the name of the `RawValue` of [serde_json] does not hold JSON text.
+    RawValueUnsupported {
+        /// JSON Pointer of the raw value.
+        path: String,
+    },
+    /// An object of strictly parsed JSON text has the same key twice.
+    DuplicateKey {
+        /// JSON Pointer of the second property with the key.
+        path: String,
+    },
+    /// The `Serialize` implementation of a value failed.
+    Custom {
+        /// JSON Pointer of the value.
+        path: String,
+        /// The message of the implementation.
+        message: String,
+    },
+    /// Strictly parsed JSON text is invalid inside of the document.
+    Strict {
+        /// JSON Pointer of the offending value.
+        path: String,
+        /// The error of parsing the value.
+        source: serde_json::Error,
+    },
+    /// The JSON text could not be parsed.
+    Json(serde_json::Error),
+    /// Writing the output failed.
+    Io(io::Error),
+}
+
+impl Error {
+    /// The JSON Pointer of the value the error is about, if it is about a value.
+    pub fn path(&self) -> Option<&str> {
+        match self {
+            Error::NonFiniteNumber { path }
+            | Error::NonStringKey { path }
+            | Error::LossyInteger { path, .. }
+            | Error::RawValueUnsupported { path }
+            | Error::DuplicateKey { path }
+            | Error::Strict { path, .. }
+            | Error::Custom { path, .. } => Some(path),
+            Error::Json(_) | Error::Io(_) => None,
+        }
+    }
+
+    /// Prepends the key of the property the error happened in to the path.
+    pub(crate) fn in_property(self, key: &str) -> Self {
+        self.prepend(&key.replace('~', "~0").replace('/', "~1"))
+    }
+
+    /// Prepends the index of the array element the error happened in to the path.
+    pub(crate) fn in_element(self, index: usize) -> Self {
+        self.prepend(&index.to_string())
+    }
+
+    fn prepend(mut self, segment: &str) -> Self {
+        if let Error::NonFiniteNumber { path }
+        | Error::NonStringKey { path }
+        | Error::LossyInteger { path, .. }
+        | Error::RawValueUnsupported { path }
+        | Error::DuplicateKey { path }
+        | Error::Strict { path, .. }
+        | Error::Custom { path, .. } = &mut self
+        {
+            path.insert_str(0, segment);
+            path.insert(0, '/');
+        }
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NonFiniteNumber { .. } => {
+                f.write_str("NaN and +/-Infinity are not permitted in JSON")?
+            }
+            Error::NonStringKey { .. } => f.write_str("key must be a string")?,
+            Error::LossyInteger { value, .. } => write!(
+                f,
+                "Integer {value} is outside of the I-JSON range that doubles represent exactly"
+            )?,
+            Error::RawValueUnsupported { .. } => {
+                f.write_str("raw value does not hold JSON text")?
+            }
+            Error::DuplicateKey { .. } => f.write_str("duplicate object key")?,
+            Error::Strict { source, .. } => write!(f, "{source}")?,
+            Error::Custom { message, .. } => f.write_str(message)?,
+            Error::Json(error) => return write!(f, "{error}"),
+            Error::Io(error) => return write!(f, "{error}"),
+        }
+        match self.path() {
+            Some(path) if !path.is_empty() => write!(f, " at `{path}`"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl StdError for Error {
+    #[cfg(feature = "std")]
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Strict { source, .. } | Error::Json(source) => Some(source),
+            Error::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl serde::ser::Error for Error {
+    fn custom<T: fmt::Display>(message: T) -> Self {
+        Error::Custom {
+            path: String::new(),
+            message: message.to_string(),
+        }
+    }
+}
+
+impl From<serde_json::Error> for Error {
+    fn from(error: serde_json::Error) -> Self {
+        Error::Json(error)
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Error::Io(error)
+    }
+}
+
+/// Converts the error into an I/O error, with the same kinds as [serde_json] errors have.
+#[cfg(feature = "std")]
+impl From<Error> for std::io::Error {
+    fn from(error: Error) -> Self {
+        use std::io::ErrorKind;
+
+        match error {
+            Error::Io(error) => error,
+            Error::Json(error) => error.into(),
+            Error::NonFiniteNumber { .. }
+            | Error::LossyInteger { .. }
+            | Error::RawValueUnsupported { .. } => Self::new(ErrorKind::InvalidInput, error),
+            error => Self::new(ErrorKind::InvalidData, error),
+        }
+    }
+}
+
+/// Result of canonicalizing a value as JCS.
+pub(crate) type Result<T> = core::result::Result<T, Error>;
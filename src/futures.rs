@@ -0,0 +1,3 @@
+//! Canonicalization into a futures [AsyncWrite].
+
+crate::async_io::async_io!("futures", futures_util::io);
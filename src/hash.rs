@@ -0,0 +1,108 @@
+//! Hashing the JCS serialization without materializing it in a buffer.
+//!
+//! Works with any hash function implementing the RustCrypto [Digest] trait.
+
+use digest::{Digest, Output};
+use serde::Serialize;
+
+use crate::{io, to_writer, Error};
+
+/// An [io::Write] adapter that feeds everything written into a [Digest].
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::{to_writer, HashWriter};
+/// use sha2::{Digest, Sha256};
+///
+/// let mut writer = HashWriter::new(Sha256::new());
+/// to_writer(&serde_json::json!({"b": 2, "a": 1}), &mut writer).unwrap();
+///
+/// assert_eq!(writer.finalize(), Sha256::digest(br#"{"a":1,"b":2}"#));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HashWriter<D> {
+    hasher: D,
+}
+
+impl<D: Digest> HashWriter<D> {
+    /// Creates a writer feeding the given hasher.
+    pub fn new(hasher: D) -> Self {
+        Self { hasher }
+    }
+
+    /// Consumes the writer returning the hasher.
+    pub fn into_inner(self) -> D {
+        self.hasher
+    }
+
+    /// Consumes the writer returning the hash of everything written into it.
+    pub fn finalize(self) -> Output<D> {
+        self.hasher.finalize()
+    }
+}
+
+impl<D: Digest> io::Write for HashWriter<D> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.update(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Feeds a borrowed hasher, [HashWriter] owns its hasher.
+struct BorrowedHashWriter<'a, D> {
+    hasher: &'a mut D,
+}
+
+impl<D: Digest> io::Write for BorrowedHashWriter<'_, D> {
+    #[inline]
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.hasher.update(buf);
+        Ok(buf.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Serialize the given data structure as JCS and return its hash.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::to_digest;
+/// use sha2::{Digest, Sha256};
+///
+/// let hash = to_digest::<Sha256>(&serde_json::json!({"b": 2, "a": 1})).unwrap();
+///
+/// assert_eq!(hash, Sha256::digest(br#"{"a":1,"b":2}"#));
+/// ```
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys.
+pub fn to_digest<D: Digest>(value: &impl Serialize) -> Result<Output<D>, Error> {
+    let mut writer = HashWriter::new(D::new());
+    to_writer(value, &mut writer)?;
+    Ok(writer.finalize())
+}
+
+/// Serialize the given data structure as JCS into the hasher.
+///
+/// Allows hashing the canonical form together with other data.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys. The hasher may have been
+/// updated with part of the serialization in that case.
+pub fn to_hasher<D: Digest>(value: &impl Serialize, hasher: &mut D) -> Result<(), Error> {
+    to_writer(value, &mut BorrowedHashWriter { hasher })
+}
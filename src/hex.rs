@@ -0,0 +1,11 @@
+//! Lowercase hexadecimal encoding of hashes, shared by the library and the `jcs` binary.
+
+use std::fmt::Write as _;
+
+/// Encodes the bytes as lowercase hexadecimal.
+pub(crate) fn encode(bytes: &[u8]) -> String {
+    bytes.iter().fold(String::new(), |mut hex, byte| {
+        let _ = write!(hex, "{byte:02x}");
+        hex
+    })
+}
@@ -0,0 +1,123 @@
+//! The parts of `std::io` that the serializer writes with.
+//!
+//! With the `std` feature these are the types of [std::io]. Without it a minimal [Write] trait
+//! with the same methods is provided instead, implemented for `Vec<u8>`, along with an [Error]
+//! type that carries the reason of the failure.
+
+#[cfg(feature = "std")]
+pub use std::io::{Error, ErrorKind, Result, Write};
+
+#[cfg(not(feature = "std"))]
+pub use self::core_io::{Error, ErrorKind, Result, Write};
+
+#[cfg(not(feature = "std"))]
+mod core_io {
+    use alloc::{string::String, vec::Vec};
+    use core::fmt;
+
+    /// The kind of an [Error], the subset of the ones of `std::io` that the serializer uses.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    #[non_exhaustive]
+    pub enum ErrorKind {
+        /// The data to write is not valid.
+        InvalidInput,
+        /// The data read or written is not valid.
+        InvalidData,
+        /// The writer did not accept any more bytes.
+        WriteZero,
+        /// Any other failure.
+        Other,
+    }
+
+    /// Error of writing the serialized JCS.
+    #[derive(Debug)]
+    pub struct Error {
+        kind: ErrorKind,
+        message: String,
+    }
+
+    impl Error {
+        /// Creates an error of the given kind with a message describing it.
+        pub fn new<M: Into<String>>(kind: ErrorKind, message: M) -> Self {
+            Self {
+                kind,
+                message: message.into(),
+            }
+        }
+
+        /// The kind of the error.
+        pub fn kind(&self) -> ErrorKind {
+            self.kind
+        }
+    }
+
+    impl fmt::Display for Error {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str(&self.message)
+        }
+    }
+
+    /// Result of writing the serialized JCS.
+    pub type Result<T> = core::result::Result<T, Error>;
+
+    /// A byte sink, with the methods of `std::io::Write` that the serializer uses.
+    pub trait Write {
+        /// Writes some of the bytes of the buffer, returning how many were written.
+        fn write(&mut self, buf: &[u8]) -> Result<usize>;
+
+        /// Flushes the bytes written so far to their destination.
+        fn flush(&mut self) -> Result<()>;
+
+        /// Writes the whole buffer.
+        fn write_all(&mut self, mut buf: &[u8]) -> Result<()> {
+            while !buf.is_empty() {
+                match self.write(buf)? {
+                    0 => {
+                        return Err(Error::new(
+                            ErrorKind::WriteZero,
+                            "failed to write whole buffer",
+                        ))
+                    }
+                    written => buf = &buf[written..],
+                }
+            }
+            Ok(())
+        }
+    }
+
+    impl Write for Vec<u8> {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            self.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> {
+            Ok(())
+        }
+
+        #[inline]
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            self.extend_from_slice(buf);
+            Ok(())
+        }
+    }
+
+    impl<W: ?Sized + Write> Write for &mut W {
+        #[inline]
+        fn write(&mut self, buf: &[u8]) -> Result<usize> {
+            (**self).write(buf)
+        }
+
+        #[inline]
+        fn flush(&mut self) -> Result<()> {
+            (**self).flush()
+        }
+
+        #[inline]
+        fn write_all(&mut self, buf: &[u8]) -> Result<()> {
+            (**self).write_all(buf)
+        }
+    }
+}
@@ -1,95 +1,350 @@
-use std::{
-    collections::BTreeSet,
-    fmt::Display,
-    io::{self, Write},
+use alloc::{
+    string::{String, ToString},
+    vec::Vec,
 };
+use core::{fmt::Display, ops::Range};
 
-use serde::{ser::Serializer as SerSerializer, Serialize};
-use serde_json::{
-    error::Result,
-    ser::{CharEscape, Formatter, Serializer},
+use crate::{
+    error::{Error, Result},
+    io,
+    strict::strict_value,
+};
+use serde::{
+    ser::{self, Impossible, SerializeMap as SerializeMapTrait, Serializer as SerSerializer},
+    Serialize,
 };
 
-struct JsonProperty {
-    sorting_key: Vec<u16>,
-    key: Vec<u8>,
-    value: Vec<u8>,
-}
-
-impl JsonProperty {
-    fn new(key: Vec<u8>, value: Vec<u8>) -> io::Result<Self> {
-        // Go through deserialization again to process escape sequences in the key
-        // "\\a" should be processed as '\a' for sorting
-        let sorting_key_as_value = serde_json::from_slice::<serde_json::Value>(&key)?;
-        let sorting_key: Vec<u16> = sorting_key_as_value
-            .as_str()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF8 sequence"))?
-            .encode_utf16()
-            .collect();
-        Ok(Self {
-            sorting_key,
-            key,
-            value,
-        })
-    }
+/// Size of the chunks of the [Arena].
+const ARENA_CHUNK: usize = 64 * 1024;
+
+/// Nested objects up to this size in the arena are sorted as soon as they end.
+///
+/// Keeping track of the properties takes more memory than the bytes of small objects, so they are
+/// copied once more instead, and only larger objects are left to be written out in sorted order
+/// at the end of the outermost object.
+const SMALL_OBJECT: usize = 4 * 1024;
+
+/// Append only byte storage in chunks, so that growing it never moves the bytes already written.
+#[derive(Default)]
+struct Arena {
+    chunks: Vec<Vec<u8>>,
+    len: usize,
 }
 
-impl PartialEq for JsonProperty {
-    fn eq(&self, other: &Self) -> bool {
-        self.sorting_key == other.sorting_key
+impl Arena {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn extend(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            let index = self.len / ARENA_CHUNK;
+            if index == self.chunks.len() {
+                // the first chunk grows on demand, so small objects don't allocate a full chunk
+                self.chunks.push(match index {
+                    0 => Vec::new(),
+                    _ => Vec::with_capacity(ARENA_CHUNK),
+                });
+            }
+            let chunk = &mut self.chunks[index];
+            let count = bytes.len().min(ARENA_CHUNK - chunk.len());
+            chunk.extend_from_slice(&bytes[..count]);
+            bytes = &bytes[count..];
+            self.len += count;
+        }
+    }
+
+    fn byte(&self, position: usize) -> u8 {
+        self.chunks[position / ARENA_CHUNK][position % ARENA_CHUNK]
+    }
+
+    fn write_range<W>(&self, writer: &mut W, range: Range<usize>) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let mut position = range.start;
+        while position < range.end {
+            let offset = position % ARENA_CHUNK;
+            let count = (range.end - position).min(ARENA_CHUNK - offset);
+            writer.write_all(&self.chunks[position / ARENA_CHUNK][offset..offset + count])?;
+            position += count;
+        }
+        Ok(())
+    }
+
+    /// Shortens the arena, keeping the chunks allocated.
+    fn truncate(&mut self, len: usize) {
+        for (index, chunk) in self.chunks.iter_mut().enumerate().skip(len / ARENA_CHUNK) {
+            chunk.truncate(len.saturating_sub(index * ARENA_CHUNK));
+        }
+        self.len = len;
+    }
+
+    /// Empties the arena, keeping the chunks for reuse.
+    fn clear(&mut self) {
+        self.truncate(0);
     }
 }
 
-impl Eq for JsonProperty {}
+/// The bytes of an object key written into the [Arena], with escape sequences decoded, up to the
+/// closing quote.
+///
+/// The UTF-8 lead bytes of U+E000 to U+FFFF are moved after the ones of the supplementary planes,
+/// so that comparing the bytes orders the keys by their UTF-16 code units, as RFC 8785 requires.
+struct KeyBytes<'a> {
+    arena: &'a Arena,
+    position: usize,
+}
 
-impl PartialOrd for JsonProperty {
-    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
-        Some(self.cmp(other))
+impl<'a> KeyBytes<'a> {
+    /// The key starting with the opening quote at `start`.
+    fn new(arena: &'a Arena, start: usize) -> Self {
+        Self {
+            arena,
+            position: start + 1,
+        }
+    }
+
+    /// Position after the closing quote of the key.
+    fn end(mut self) -> usize {
+        self.by_ref().for_each(drop);
+        self.position + 1
     }
 }
 
-impl Ord for JsonProperty {
-    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
-        self.sorting_key.cmp(&other.sorting_key)
+impl Iterator for KeyBytes<'_> {
+    type Item = u8;
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.arena.byte(self.position);
+        if byte == b'"' {
+            return None;
+        }
+        self.position += 1;
+        match byte {
+            // the formatter only writes the escapes of quotes, backslashes and control characters
+            b'\\' => {
+                let escape = self.arena.byte(self.position);
+                self.position += 1;
+                Some(match escape {
+                    b'b' => b'\x08',
+                    b'f' => b'\x0c',
+                    b'n' => b'\n',
+                    b'r' => b'\r',
+                    b't' => b'\t',
+                    b'u' => {
+                        let hex = |position| match self.arena.byte(position) {
+                            digit @ b'0'..=b'9' => digit - b'0',
+                            digit => digit - b'a' + 10,
+                        };
+                        let byte = hex(self.position + 2) << 4 | hex(self.position + 3);
+                        self.position += 4;
+                        byte
+                    }
+                    escape => escape,
+                })
+            }
+            0xEE | 0xEF => Some(byte + 7),
+            byte => Some(byte),
+        }
     }
 }
 
-type JsonObject = BTreeSet<JsonProperty>;
+/// An object that has ended.
+struct JsonObject {
+    /// Where the object is in the arena. Its properties are in between in the order they were
+    /// written, without the braces and separators.
+    span: Range<usize>,
+    /// The properties of the object in `properties`.
+    properties: Range<usize>,
+    /// Index of the first object in `objects` that is not nested in this one.
+    next: usize,
+}
+
+/// An object being written.
+struct OpenObject {
+    /// Index of the object in `objects`.
+    index: usize,
+    /// Index of its first property in `pending_properties`.
+    pending_properties: usize,
+    /// Length of `properties` when the object began, the objects nested in it add theirs after.
+    properties: usize,
+}
+
+/// Compares object keys by their UTF-16 code units, the property order required by RFC 8785.
+///
+/// Compares the UTF-8 bytes with the lead bytes of U+E000 to U+FFFF moved after the ones of the
+/// supplementary planes, the same way as [KeyBytes].
+pub(crate) fn compare_keys(a: &str, b: &str) -> core::cmp::Ordering {
+    let utf16_order = |byte: u8| match byte {
+        0xEE | 0xEF => byte + 7,
+        byte => byte,
+    };
+    a.bytes().map(utf16_order).cmp(b.bytes().map(utf16_order))
+}
+
+fn invalid_call(message: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
 
 /// The formatter that's used by the [JcsSerializer].
 ///
 /// This formatter is not fully RFC 8785 compliant in its own right, because the [JcsSerializer] is
 /// instead responsible for handling floating point NaN and infinity.
+///
+/// An object cannot be written out before all of its properties are known. Everything inside of
+/// the outermost object goes into the arena as it is written, and objects and properties refer to
+/// it with index ranges. When the outermost object ends it is written out with the properties of
+/// every object in sorted order, so each byte is copied once into the arena and once into the
+/// writer.
 #[derive(Default)]
 pub(crate) struct JcsFormatter {
+    arena: Arena,
+    /// Objects in the order they begin, which is the order of their positions in the arena.
     objects: Vec<JsonObject>,
-    keys: Vec<Vec<u8>>,
-    buffers: Vec<Vec<u8>>,
+    /// Ranges of the properties of the objects that have ended in the arena, sorted per object.
+    properties: Vec<Range<usize>>,
+    open_objects: Vec<OpenObject>,
+    pending_properties: Vec<Range<usize>>,
+    /// Start of each property being written in the arena.
+    open_properties: Vec<usize>,
+    /// Buffer for sorting small objects.
+    small_object: Vec<u8>,
+    /// Of duplicate keys keep the last one, the way [serde_json::Value] does, instead of the first.
+    keep_last_duplicate: bool,
 }
 
 impl JcsFormatter {
-    fn get_writer<'a, W>(&'a mut self, writer: &'a mut W) -> Box<dyn io::Write + 'a>
+    /// A formatter that keeps the last of duplicate keys, for canonicalizing JSON text the same
+    /// way as parsing it into a [serde_json::Value] first.
+    #[cfg(feature = "std")]
+    pub(crate) fn keeping_last_duplicate() -> Self {
+        Self {
+            keep_last_duplicate: true,
+            ..Self::default()
+        }
+    }
+
+    /// Drops the state of a serialization that failed, keeping the buffers for reuse.
+    pub(crate) fn reset(&mut self) {
+        self.arena.clear();
+        self.objects.clear();
+        self.properties.clear();
+        self.open_objects.clear();
+        self.pending_properties.clear();
+        self.open_properties.clear();
+    }
+
+    /// Drops the property whose key failed to serialize.
+    pub(crate) fn discard_property(&mut self) {
+        self.open_properties.pop();
+    }
+
+    /// Drops the property whose value failed to serialize, returning its key.
+    pub(crate) fn fail_property(&mut self) -> Option<String> {
+        let start = self.open_properties.pop()?;
+        let end = KeyBytes::new(&self.arena, start).end();
+        let key: Vec<u8> = (start..end)
+            .map(|position| self.arena.byte(position))
+            .collect();
+        serde_json::from_slice(&key).ok()
+    }
+
+    /// Writes into the arena inside of objects, or into the writer outside of them.
+    #[inline]
+    fn write_all<W>(&mut self, writer: &mut W, bytes: &[u8]) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        match self.buffers.last_mut() {
-            Some(buffer) => Box::new(buffer),
-            None => Box::new(writer),
+        if self.open_objects.is_empty() {
+            writer.write_all(bytes)
+        } else {
+            self.arena.extend(bytes);
+            Ok(())
+        }
+    }
+
+    /// Sorts the properties of an object that ended and moves them to `properties`. Of duplicate
+    /// keys, only the first one is kept, or the last one if `keep_last_duplicate` is set.
+    fn sort_properties(&mut self, first: usize) -> Range<usize> {
+        let arena = &self.arena;
+        let key = |property: &Range<usize>| KeyBytes::new(arena, property.start);
+        // ordering equal keys by position keeps the unstable sort from allocating a buffer
+        let keep_last_duplicate = self.keep_last_duplicate;
+        self.pending_properties[first..].sort_unstable_by(|a, b| {
+            let position = match keep_last_duplicate {
+                true => b.start.cmp(&a.start),
+                false => a.start.cmp(&b.start),
+            };
+            key(a).cmp(key(b)).then(position)
+        });
+
+        let start = self.properties.len();
+        for property in self.pending_properties.drain(first..) {
+            match self.properties[start..].last() {
+                Some(last) if key(last).eq(key(&property)) => {}
+                _ => self.properties.push(property),
+            }
         }
+        start..self.properties.len()
+    }
+
+    /// Writes out an object that has ended, with its properties in sorted order.
+    fn write_object<W>(&self, writer: &mut W, object: &JsonObject) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        writer.write_all(b"{")?;
+        for (idx, property) in self.properties[object.properties.clone()]
+            .iter()
+            .enumerate()
+        {
+            if idx > 0 {
+                writer.write_all(b",")?;
+            }
+            let key_end = KeyBytes::new(&self.arena, property.start).end();
+            self.arena.write_range(writer, property.start..key_end)?;
+            writer.write_all(b":")?;
+            self.write_value(writer, key_end..property.end)?;
+        }
+        writer.write_all(b"}")
+    }
+
+    /// Writes out a property value, along with the objects nested in it.
+    fn write_value<W>(&self, writer: &mut W, value: Range<usize>) -> io::Result<()>
+    where
+        W: ?Sized + io::Write,
+    {
+        let mut position = value.start;
+        let mut index = self
+            .objects
+            .partition_point(|object| object.span.start < position);
+        while let Some(object) = self
+            .objects
+            .get(index)
+            .filter(|object| object.span.start <= value.end)
+        {
+            self.arena
+                .write_range(writer, position..object.span.start)?;
+            self.write_object(writer, object)?;
+            position = object.span.end;
+            index = object.next;
+        }
+        self.arena.write_range(writer, position..value.end)
     }
 }
 
-impl Formatter for JcsFormatter {
+impl JcsFormatter {
     /// Writes a `null` value to the specified writer.
-    fn write_null<W>(&mut self, writer: &mut W) -> io::Result<()>
+    pub(crate) fn write_null<W>(&mut self, writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        self.get_writer(writer).write_all(b"null")
+        self.write_all(writer, b"null")
     }
 
     /// Writes a `true` or `false` value to the specified writer.
-    fn write_bool<W>(&mut self, writer: &mut W, value: bool) -> io::Result<()>
+    pub(crate) fn write_bool<W>(&mut self, writer: &mut W, value: bool) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -98,12 +353,12 @@ impl Formatter for JcsFormatter {
         } else {
             b"false" as &[u8]
         };
-        self.get_writer(writer).write_all(s)
+        self.write_all(writer, s)
     }
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_i8<W>(&mut self, writer: &mut W, value: i8) -> io::Result<()>
+    pub(crate) fn write_i8<W>(&mut self, writer: &mut W, value: i8) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -112,7 +367,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_i16<W>(&mut self, writer: &mut W, value: i16) -> io::Result<()>
+    pub(crate) fn write_i16<W>(&mut self, writer: &mut W, value: i16) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -121,7 +376,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_i32<W>(&mut self, writer: &mut W, value: i32) -> io::Result<()>
+    pub(crate) fn write_i32<W>(&mut self, writer: &mut W, value: i32) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -130,7 +385,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_i64<W>(&mut self, writer: &mut W, value: i64) -> io::Result<()>
+    pub(crate) fn write_i64<W>(&mut self, writer: &mut W, value: i64) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -139,7 +394,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_i128<W>(&mut self, writer: &mut W, value: i128) -> io::Result<()>
+    pub(crate) fn write_i128<W>(&mut self, writer: &mut W, value: i128) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -148,7 +403,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_u8<W>(&mut self, writer: &mut W, value: u8) -> io::Result<()>
+    pub(crate) fn write_u8<W>(&mut self, writer: &mut W, value: u8) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -157,7 +412,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_u16<W>(&mut self, writer: &mut W, value: u16) -> io::Result<()>
+    pub(crate) fn write_u16<W>(&mut self, writer: &mut W, value: u16) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -166,7 +421,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_u32<W>(&mut self, writer: &mut W, value: u32) -> io::Result<()>
+    pub(crate) fn write_u32<W>(&mut self, writer: &mut W, value: u32) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -175,7 +430,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_u64<W>(&mut self, writer: &mut W, value: u64) -> io::Result<()>
+    pub(crate) fn write_u64<W>(&mut self, writer: &mut W, value: u64) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -184,7 +439,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_u128<W>(&mut self, writer: &mut W, value: u128) -> io::Result<()>
+    pub(crate) fn write_u128<W>(&mut self, writer: &mut W, value: u128) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -193,7 +448,7 @@ impl Formatter for JcsFormatter {
 
     /// Javascript (and as a consequence JSON) only supports a single numeric
     /// type which is the double. Format all numbers according to this.
-    fn write_f32<W>(&mut self, writer: &mut W, value: f32) -> io::Result<()>
+    pub(crate) fn write_f32<W>(&mut self, writer: &mut W, value: f32) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -202,14 +457,14 @@ impl Formatter for JcsFormatter {
 
     /// Writes a floating point value like `-31.26e+12` to the specified writer.
     /// JCS (and JSON in general) does not permit NaN or (-)Infinity
-    fn write_f64<W>(&mut self, writer: &mut W, value: f64) -> io::Result<()>
+    pub(crate) fn write_f64<W>(&mut self, writer: &mut W, value: f64) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
         if value.is_finite() {
             let mut buffer = ryu_js::Buffer::new();
             let s = buffer.format_finite(value);
-            self.get_writer(writer).write_all(s.as_bytes())
+            self.write_all(writer, s.as_bytes())
         } else {
             Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
@@ -218,48 +473,43 @@ impl Formatter for JcsFormatter {
         }
     }
 
-    /// Writes a number that has already been rendered to a string.
-    /// To be JCS conformant the string is parsed into a double
-    /// and reformatted.
-    fn write_number_str<W>(&mut self, writer: &mut W, value: &str) -> io::Result<()>
-    where
-        W: ?Sized + io::Write,
-    {
-        let number: f64 = value
-            .parse()
-            .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "Cannot parse str to f64"))?;
-        self.write_f64(writer, number)
-    }
-
     /// Called before each series of `write_string_fragment` and
     /// `write_char_escape`.  Writes a `"` to the specified writer.
-    fn begin_string<W>(&mut self, writer: &mut W) -> io::Result<()>
+    pub(crate) fn begin_string<W>(&mut self, writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        self.get_writer(writer).write_all(b"\"")
+        self.write_all(writer, b"\"")
     }
 
     /// Called after each series of `write_string_fragment` and
     /// `write_char_escape`.  Writes a `"` to the specified writer.
-    fn end_string<W>(&mut self, writer: &mut W) -> io::Result<()>
+    pub(crate) fn end_string<W>(&mut self, writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        self.get_writer(writer).write_all(b"\"")
+        self.write_all(writer, b"\"")
     }
 
     /// Writes a string fragment that doesn't need any escaping to the
     /// specified writer.
-    fn write_string_fragment<W>(&mut self, writer: &mut W, fragment: &str) -> io::Result<()>
+    pub(crate) fn write_string_fragment<W>(
+        &mut self,
+        writer: &mut W,
+        fragment: &str,
+    ) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        self.get_writer(writer).write_all(fragment.as_bytes())
+        self.write_all(writer, fragment.as_bytes())
     }
 
     /// Writes a character escape code to the specified writer.
-    fn write_char_escape<W>(&mut self, writer: &mut W, char_escape: CharEscape) -> io::Result<()>
+    pub(crate) fn write_char_escape<W>(
+        &mut self,
+        writer: &mut W,
+        char_escape: CharEscape,
+    ) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
@@ -268,13 +518,6 @@ impl Formatter for JcsFormatter {
         let s = match char_escape {
             Quote => b"\\\"",
             ReverseSolidus => b"\\\\",
-            Solidus => {
-                // This is according to the javascript reference implementation (https://www.npmjs.com/package/canonicalize) where
-                // an escaped solidus is turned into a non escaped one, in javascript "\/" === "/".
-                // RFC 8785 in Section 3.2.2.2 does not list a solidus as a special escape character.
-                // Because of the return type of the match block we need to return here early
-                return self.get_writer(writer).write_all(b"/");
-            }
             Backspace => b"\\b",
             FormFeed => b"\\f",
             LineFeed => b"\\n",
@@ -291,348 +534,540 @@ impl Formatter for JcsFormatter {
                     HEX_DIGITS[(byte & 0xF) as usize],
                 ];
 
-                return self.get_writer(writer).write_all(bytes);
+                return self.write_all(writer, bytes);
             }
         };
 
-        self.get_writer(writer).write_all(s)
+        self.write_all(writer, s)
     }
 
     /// Called before every array.  Writes a `[` to the specified
     /// writer.
     #[inline]
-    fn begin_array<W>(&mut self, writer: &mut W) -> io::Result<()>
+    pub(crate) fn begin_array<W>(&mut self, writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        self.get_writer(writer).write_all(b"[")
+        self.write_all(writer, b"[")
     }
 
     /// Called after every array.  Writes a `]` to the specified
     /// writer.
     #[inline]
-    fn end_array<W>(&mut self, writer: &mut W) -> io::Result<()>
+    pub(crate) fn end_array<W>(&mut self, writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        self.get_writer(writer).write_all(b"]")
+        self.write_all(writer, b"]")
     }
 
     /// Called before every array value.  Writes a `,` if needed to
     /// the specified writer.
     #[inline]
-    fn begin_array_value<W>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
+    pub(crate) fn begin_array_value<W>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
         if first {
             Ok(())
         } else {
-            self.get_writer(writer).write_all(b",")
+            self.write_all(writer, b",")
         }
     }
 
     /// Called after every array value.
     #[inline]
-    fn end_array_value<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    pub(crate) fn end_array_value<W>(&mut self, _writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
         Ok(())
     }
 
-    /// Pushes a new empty object to the stack
-    #[inline]
-    fn begin_object<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    /// Writes bytes as an array of numbers.
+    pub(crate) fn write_byte_array<W>(&mut self, writer: &mut W, value: &[u8]) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        self.objects.push(Default::default());
-        Ok(())
+        self.begin_array(writer)?;
+        for (index, byte) in value.iter().enumerate() {
+            self.begin_array_value(writer, index == 0)?;
+            self.write_u8(writer, *byte)?;
+            self.end_array_value(writer)?;
+        }
+        self.end_array(writer)
     }
 
-    /// Writes out the whole object with sorted properties
+    /// Starts collecting the properties of a new object
     #[inline]
-    fn end_object<W>(&mut self, writer: &mut W) -> io::Result<()>
+    pub(crate) fn begin_object<W>(&mut self, _writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        let json_object = self.objects.pop().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "end_object called before start_object",
-            )
-        })?;
-        let mut writer = self.get_writer(writer);
-        writer.write_all(b"{")?;
-        json_object
-            .into_iter()
-            .enumerate()
-            .try_for_each(|(idx, property)| {
-                if idx > 0 {
-                    writer.write_all(b",")?;
-                }
-                writer.write_all(&property.key)?;
-                writer.write_all(b":")?;
-                writer.write_all(&property.value)
-            })?;
-        writer.write_all(b"}")
+        self.open_objects.push(OpenObject {
+            index: self.objects.len(),
+            pending_properties: self.pending_properties.len(),
+            properties: self.properties.len(),
+        });
+        self.objects.push(JsonObject {
+            span: self.arena.len()..self.arena.len(),
+            properties: 0..0,
+            next: 0,
+        });
+        Ok(())
     }
 
-    /// Creates a new buffer to direct writes into
+    /// Sorts the properties of the object, and writes out the outermost object
     #[inline]
-    fn begin_object_key<W>(&mut self, _writer: &mut W, _first: bool) -> io::Result<()>
+    pub(crate) fn end_object<W>(&mut self, writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        self.buffers.push(Default::default());
-        Ok(())
+        let open = self
+            .open_objects
+            .pop()
+            .ok_or_else(|| invalid_call("end_object called before start_object"))?;
+        let properties = self.sort_properties(open.pending_properties);
+        let next = self.objects.len();
+        let object = &mut self.objects[open.index];
+        object.span.end = self.arena.len();
+        object.properties = properties;
+        object.next = next;
+        let object = &self.objects[open.index];
+
+        if self.open_objects.is_empty() {
+            let written = self.write_object(writer, object);
+            self.arena.clear();
+            self.objects.clear();
+            self.properties.clear();
+            written
+        } else if object.span.len() <= SMALL_OBJECT {
+            let mut sorted = core::mem::take(&mut self.small_object);
+            sorted.clear();
+            self.write_object(&mut sorted, object)?;
+            self.arena.truncate(object.span.start);
+            self.objects.truncate(open.index);
+            self.properties.truncate(open.properties);
+            self.arena.extend(&sorted);
+            self.small_object = sorted;
+            Ok(())
+        } else {
+            Ok(())
+        }
     }
 
-    /// Moves the last buffer to the top of keys stack
+    /// Remembers where the property starts
     #[inline]
-    fn end_object_key<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    pub(crate) fn begin_object_key<W>(&mut self, _writer: &mut W, _first: bool) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        let key = self.buffers.pop().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "end_object_key called before begin_object_key",
-            )
-        })?;
-        self.keys.push(key);
+        self.open_properties.push(self.arena.len());
         Ok(())
     }
 
-    /// Creates a new buffer to direct writes into
+    /// Does nothing, the key is found in the arena when it is needed
     #[inline]
-    fn begin_object_value<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    pub(crate) fn end_object_key<W>(&mut self, _writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        self.buffers.push(Vec::new());
         Ok(())
     }
 
-    /// Creates a JsonProperty from the key-value last created and adds it to the top object on the stack
+    /// Does nothing, the `:` is written out with the object
     #[inline]
-    fn end_object_value<W>(&mut self, _writer: &mut W) -> io::Result<()>
+    pub(crate) fn begin_object_value<W>(&mut self, _writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        let value = self.buffers.pop().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "end_object_value called before begin_object_value",
-            )
-        })?;
-        let key = self.keys.pop().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "end_object_value called before end_object_key",
-            )
-        })?;
-        let json_object = self.objects.last_mut().ok_or_else(|| {
-            io::Error::new(
-                io::ErrorKind::InvalidData,
-                "end_object_value called before start_object",
-            )
-        })?;
-        json_object.insert(JsonProperty::new(key, value)?);
         Ok(())
     }
 
-    /// Raw fragments are not supported because it cannot be verified that they conform to JCS without modifying them
+    /// Adds the property to the innermost object being written
     #[inline]
-    fn write_raw_fragment<W>(&mut self, _writer: &mut W, _fragment: &str) -> io::Result<()>
+    pub(crate) fn end_object_value<W>(&mut self, _writer: &mut W) -> io::Result<()>
     where
         W: ?Sized + io::Write,
     {
-        Err(io::Error::new(
-            io::ErrorKind::InvalidInput,
-            "Raw values are not supported for JCS serialization",
-        ))
+        let start = self
+            .open_properties
+            .pop()
+            .ok_or_else(|| invalid_call("end_object_value called before begin_object_key"))?;
+        self.pending_properties.push(start..self.arena.len());
+        Ok(())
+    }
+}
+
+/// How integers that cannot be represented exactly as a double are serialized.
+///
+/// JCS serializes every number as a double, so integers outside of the I-JSON safe range of
+/// -(2^53 - 1) to 2^53 - 1 may be rounded, and a signature would cover a different number than
+/// the one the application holds.
+///
+/// # Usage
+/// ```
+/// use serde::Serialize;
+/// use serde_json_canonicalizer::{IntegerPolicy, JcsSerializer};
+///
+/// let mut serializer =
+///     JcsSerializer::new(Vec::new()).with_integer_policy(IntegerPolicy::String);
+/// [9007199254740991u64, 9007199254740993u64].serialize(&mut serializer).unwrap();
+///
+/// assert_eq!(serializer.into_inner(), br#"[9007199254740991,"9007199254740993"]"#);
+///
+/// let mut serializer =
+///     JcsSerializer::new(Vec::new()).with_integer_policy(IntegerPolicy::Error);
+/// assert!(9007199254740993u64.serialize(&mut serializer).is_err());
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum IntegerPolicy {
+    /// Round the integer to the nearest double, the same way JavaScript's `JSON.stringify` does.
+    #[default]
+    Lossy,
+    /// Fail the serialization.
+    Error,
+    /// Serialize the exact decimal digits of the integer as a JSON string.
+    String,
+}
+
+/// The largest integer that is exactly representable as a double, along with all smaller ones.
+const MAX_SAFE_INTEGER: u128 = (1 << 53) - 1;
+
+fn lossy_integer_error<T: Display>(value: T) -> Error {
+    Error::LossyInteger {
+        path: String::new(),
+        value: value.to_string(),
     }
 }
 
 /// An RFC 8785 compatible JSON Canonicalization Scheme (JCS) serializer for [serde_json].
-pub(crate) struct JcsSerializer<W: io::Write> {
-    serializer: Serializer<W, JcsFormatter>,
+///
+/// The serializer implements [serde::Serializer] for `&mut JcsSerializer<W>`, so it can be passed
+/// to any function that accepts a generic serializer. Several values can be serialized one after
+/// another, their canonical forms are written to the writer back to back without a separator.
+///
+/// A value that fails to serialize may have been written to the writer partially, the serializer
+/// drops the rest of its state and can be used for the next value.
+///
+/// # Usage
+/// ```
+/// use serde::Serialize;
+/// use serde_json_canonicalizer::JcsSerializer;
+///
+/// let mut serializer = JcsSerializer::new(Vec::new());
+/// serde_json::json!({"b": 2, "a": 1}).serialize(&mut serializer).unwrap();
+/// let jcs = serializer.into_inner();
+///
+/// assert_eq!(jcs, br#"{"a":1,"b":2}"#);
+/// ```
+pub struct JcsSerializer<W> {
+    writer: W,
+    formatter: JcsFormatter,
+    integer_policy: IntegerPolicy,
+    /// Number of arrays and objects being serialized.
+    depth: usize,
 }
 
 impl<W: io::Write> JcsSerializer<W> {
-    /// Creates a new JSON serializer.
+    /// Creates a new JCS serializer writing into the given writer.
     #[inline]
     pub fn new(writer: W) -> Self {
         Self {
-            serializer: Serializer::with_formatter(writer, JcsFormatter::default()),
+            writer,
+            formatter: JcsFormatter::default(),
+            integer_policy: IntegerPolicy::default(),
+            depth: 0,
         }
     }
 
+    /// Sets how integers that cannot be represented exactly as a double are serialized.
+    #[inline]
+    pub fn with_integer_policy(mut self, integer_policy: IntegerPolicy) -> Self {
+        self.integer_policy = integer_policy;
+        self
+    }
+
     /// Consumes this serializer returning the underlying writer.
     #[inline]
-    #[allow(dead_code)]
     pub fn into_inner(self) -> W {
-        self.serializer.into_inner()
+        self.writer
+    }
+
+    /// Creates a serializer reusing the buffers of a formatter.
+    pub(crate) fn from_parts(
+        writer: W,
+        formatter: JcsFormatter,
+        integer_policy: IntegerPolicy,
+    ) -> Self {
+        Self {
+            writer,
+            formatter,
+            integer_policy,
+            depth: 0,
+        }
+    }
+
+    /// Consumes this serializer returning the underlying writer and the formatter.
+    pub(crate) fn into_parts(self) -> (W, JcsFormatter) {
+        (self.writer, self.formatter)
+    }
+
+    /// The writer and the formatter, for writing the tokens of a value one by one.
+    #[cfg(feature = "std")]
+    pub(crate) fn parts_mut(&mut self) -> (&mut W, &mut JcsFormatter) {
+        (&mut self.writer, &mut self.formatter)
     }
 }
 
+fn non_finite_error() -> Error {
+    Error::NonFiniteNumber {
+        path: String::new(),
+    }
+}
+
+/// Raw values are serialized by [serde_json] as a struct with this name.
+const RAW_VALUE_TOKEN: &str = "$serde_json::private::RawValue";
+
+fn raw_value_error() -> Error {
+    Error::RawValueUnsupported {
+        path: String::new(),
+    }
+}
+
+/// Characters that are written as an escape sequence in strings.
+///
+/// A solidus is not escaped, RFC 8785 does not list it as a character to escape, and the
+/// reference implementation writes an escaped solidus in the input as a plain one.
+pub(crate) enum CharEscape {
+    Quote,
+    ReverseSolidus,
+    Backspace,
+    FormFeed,
+    LineFeed,
+    CarriageReturn,
+    Tab,
+    AsciiControl(u8),
+}
+
+/// Writes a string with the escaping rules of the [JcsFormatter].
+pub(crate) fn format_escaped_str<W>(
+    writer: &mut W,
+    formatter: &mut JcsFormatter,
+    value: &str,
+) -> io::Result<()>
+where
+    W: ?Sized + io::Write,
+{
+    formatter.begin_string(writer)?;
+    let mut start = 0;
+    for (index, byte) in value.bytes().enumerate() {
+        let char_escape = match byte {
+            b'"' => CharEscape::Quote,
+            b'\\' => CharEscape::ReverseSolidus,
+            b'\x08' => CharEscape::Backspace,
+            b'\x0c' => CharEscape::FormFeed,
+            b'\n' => CharEscape::LineFeed,
+            b'\r' => CharEscape::CarriageReturn,
+            b'\t' => CharEscape::Tab,
+            0x00..=0x1f => CharEscape::AsciiControl(byte),
+            _ => continue,
+        };
+        // Only ASCII bytes are escaped, so the indices are on char boundaries
+        if start < index {
+            formatter.write_string_fragment(writer, &value[start..index])?;
+        }
+        formatter.write_char_escape(writer, char_escape)?;
+        start = index + 1;
+    }
+    if start < value.len() {
+        formatter.write_string_fragment(writer, &value[start..])?;
+    }
+    formatter.end_string(writer)
+}
+
 impl<'a, W: io::Write> SerSerializer for &'a mut JcsSerializer<W> {
-    type Ok = <&'a mut Serializer<W, JcsFormatter> as SerSerializer>::Ok;
-    type Error = <&'a mut Serializer<W, JcsFormatter> as SerSerializer>::Error;
+    type Ok = ();
+    type Error = Error;
 
-    type SerializeSeq = <&'a mut Serializer<W, JcsFormatter> as SerSerializer>::SerializeSeq;
-    type SerializeTuple = <&'a mut Serializer<W, JcsFormatter> as SerSerializer>::SerializeTuple;
-    type SerializeTupleStruct =
-        <&'a mut Serializer<W, JcsFormatter> as SerSerializer>::SerializeTupleStruct;
-    type SerializeTupleVariant =
-        <&'a mut Serializer<W, JcsFormatter> as SerSerializer>::SerializeTupleVariant;
-    type SerializeMap = <&'a mut Serializer<W, JcsFormatter> as SerSerializer>::SerializeMap;
-    type SerializeStruct = <&'a mut Serializer<W, JcsFormatter> as SerSerializer>::SerializeStruct;
-    type SerializeStructVariant =
-        <&'a mut Serializer<W, JcsFormatter> as SerSerializer>::SerializeStructVariant;
+    type SerializeSeq = SerializeSeq<'a, W>;
+    type SerializeTuple = SerializeSeq<'a, W>;
+    type SerializeTupleStruct = SerializeSeq<'a, W>;
+    type SerializeTupleVariant = SerializeSeq<'a, W>;
+    type SerializeMap = SerializeMap<'a, W>;
+    type SerializeStruct = SerializeMap<'a, W>;
+    type SerializeStructVariant = SerializeMap<'a, W>;
 
     #[inline]
     fn serialize_bool(self, value: bool) -> Result<()> {
-        self.serializer.serialize_bool(value)
+        self.formatter
+            .write_bool(&mut self.writer, value)
+            .map_err(Error::Io)
     }
 
     #[inline]
     fn serialize_i8(self, value: i8) -> Result<()> {
-        self.serializer.serialize_i8(value)
+        self.formatter
+            .write_i8(&mut self.writer, value)
+            .map_err(Error::Io)
     }
 
     #[inline]
     fn serialize_i16(self, value: i16) -> Result<()> {
-        self.serializer.serialize_i16(value)
+        self.formatter
+            .write_i16(&mut self.writer, value)
+            .map_err(Error::Io)
     }
 
     #[inline]
     fn serialize_i32(self, value: i32) -> Result<()> {
-        self.serializer.serialize_i32(value)
+        self.formatter
+            .write_i32(&mut self.writer, value)
+            .map_err(Error::Io)
     }
 
     #[inline]
     fn serialize_i64(self, value: i64) -> Result<()> {
-        self.serializer.serialize_i64(value)
+        let exact = u128::from(value.unsigned_abs()) <= MAX_SAFE_INTEGER;
+        self.serialize_integer(value, exact, |formatter, writer| {
+            formatter.write_i64(writer, value)
+        })
     }
 
     fn serialize_i128(self, value: i128) -> Result<()> {
-        self.serializer.serialize_i128(value)
+        let exact = value.unsigned_abs() <= MAX_SAFE_INTEGER;
+        self.serialize_integer(value, exact, |formatter, writer| {
+            formatter.write_i128(writer, value)
+        })
     }
 
     #[inline]
     fn serialize_u8(self, value: u8) -> Result<()> {
-        self.serializer.serialize_u8(value)
+        self.formatter
+            .write_u8(&mut self.writer, value)
+            .map_err(Error::Io)
     }
 
     #[inline]
     fn serialize_u16(self, value: u16) -> Result<()> {
-        self.serializer.serialize_u16(value)
+        self.formatter
+            .write_u16(&mut self.writer, value)
+            .map_err(Error::Io)
     }
 
     #[inline]
     fn serialize_u32(self, value: u32) -> Result<()> {
-        self.serializer.serialize_u32(value)
+        self.formatter
+            .write_u32(&mut self.writer, value)
+            .map_err(Error::Io)
     }
 
     #[inline]
     fn serialize_u64(self, value: u64) -> Result<()> {
-        self.serializer.serialize_u64(value)
+        let exact = u128::from(value) <= MAX_SAFE_INTEGER;
+        self.serialize_integer(value, exact, |formatter, writer| {
+            formatter.write_u64(writer, value)
+        })
     }
 
     fn serialize_u128(self, value: u128) -> Result<()> {
-        self.serializer.serialize_u128(value)
+        let exact = value <= MAX_SAFE_INTEGER;
+        self.serialize_integer(value, exact, |formatter, writer| {
+            formatter.write_u128(writer, value)
+        })
     }
 
     #[inline]
     fn serialize_f32(self, value: f32) -> Result<()> {
         if value.is_finite() {
-            self.serializer.serialize_f32(value)
+            self.formatter
+                .write_f32(&mut self.writer, value)
+                .map_err(Error::Io)
         } else {
-            Err(Self::Error::io(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "NaN and +/-Infinity are not permitted in JSON",
-            )))
+            Err(non_finite_error())
         }
     }
 
     #[inline]
     fn serialize_f64(self, value: f64) -> Result<()> {
         if value.is_finite() {
-            self.serializer.serialize_f64(value)
+            self.formatter
+                .write_f64(&mut self.writer, value)
+                .map_err(Error::Io)
         } else {
-            Err(Self::Error::io(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "NaN and +/-Infinity are not permitted in JSON",
-            )))
+            Err(non_finite_error())
         }
     }
 
     #[inline]
     fn serialize_char(self, value: char) -> Result<()> {
-        self.serializer.serialize_char(value)
+        self.serialize_str(value.encode_utf8(&mut [0; 4]))
     }
 
     #[inline]
     fn serialize_str(self, value: &str) -> Result<()> {
-        self.serializer.serialize_str(value)
+        format_escaped_str(&mut self.writer, &mut self.formatter, value).map_err(Error::Io)
     }
 
+    /// Bytes are serialized as an array of numbers, the same way as [serde_json] does.
     #[inline]
     fn serialize_bytes(self, value: &[u8]) -> Result<()> {
-        self.serializer.serialize_bytes(value)
+        self.formatter
+            .write_byte_array(&mut self.writer, value)
+            .map_err(Error::Io)
     }
 
     #[inline]
     fn serialize_unit(self) -> Result<()> {
-        self.serializer.serialize_unit()
+        self.formatter
+            .write_null(&mut self.writer)
+            .map_err(Error::Io)
     }
 
     #[inline]
-    fn serialize_unit_struct(self, name: &'static str) -> Result<()> {
-        self.serializer.serialize_unit_struct(name)
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        self.serialize_unit()
     }
 
     #[inline]
     fn serialize_unit_variant(
         self,
-        name: &'static str,
-        variant_index: u32,
+        _name: &'static str,
+        _variant_index: u32,
         variant: &'static str,
     ) -> Result<()> {
-        self.serializer
-            .serialize_unit_variant(name, variant_index, variant)
+        self.serialize_str(variant)
     }
 
     /// Serialize newtypes without an object wrapper.
     #[inline]
-    fn serialize_newtype_struct<T>(self, name: &'static str, value: &T) -> Result<()>
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        self.serializer.serialize_newtype_struct(name, value)
+        value.serialize(self)
     }
 
+    /// Serialize newtype variants as `{"variant":value}`.
     #[inline]
     fn serialize_newtype_variant<T>(
         self,
-        name: &'static str,
-        variant_index: u32,
+        _name: &'static str,
+        _variant_index: u32,
         variant: &'static str,
         value: &T,
     ) -> Result<()>
     where
         T: ?Sized + Serialize,
     {
-        self.serializer
-            .serialize_newtype_variant(name, variant_index, variant, value)
+        let mut map = self.serialize_map(Some(1))?;
+        map.serialize_entry(variant, value)?;
+        SerializeMapTrait::end(map)
     }
 
     #[inline]
     fn serialize_none(self) -> Result<()> {
-        self.serializer.serialize_none()
+        self.serialize_unit()
     }
 
     #[inline]
@@ -640,66 +1075,681 @@ impl<'a, W: io::Write> SerSerializer for &'a mut JcsSerializer<W> {
     where
         T: ?Sized + Serialize,
     {
-        self.serializer.serialize_some(value)
+        value.serialize(self)
     }
 
     #[inline]
-    fn serialize_seq(self, len: Option<usize>) -> Result<Self::SerializeSeq> {
-        self.serializer.serialize_seq(len)
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        self.formatter
+            .begin_array(&mut self.writer)
+            .map_err(|error| self.fail(Error::Io(error)))?;
+        self.depth += 1;
+        Ok(SerializeSeq {
+            ser: self,
+            len: 0,
+            variant: None,
+        })
     }
 
     #[inline]
     fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple> {
-        self.serializer.serialize_tuple(len)
+        self.serialize_seq(Some(len))
     }
 
     #[inline]
     fn serialize_tuple_struct(
         self,
-        name: &'static str,
+        _name: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleStruct> {
-        self.serializer.serialize_tuple_struct(name, len)
+        self.serialize_seq(Some(len))
     }
 
+    /// Serialize tuple variants as `{"variant":[...]}`.
     #[inline]
     fn serialize_tuple_variant(
         self,
-        name: &'static str,
-        variant_index: u32,
+        _name: &'static str,
+        _variant_index: u32,
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeTupleVariant> {
-        self.serializer
-            .serialize_tuple_variant(name, variant_index, variant, len)
+        if let Err(error) = self.begin_variant(variant) {
+            return Err(self.fail(error));
+        }
+        let mut seq = self.serialize_seq(Some(len))?;
+        seq.variant = Some(variant);
+        Ok(seq)
     }
 
     #[inline]
-    fn serialize_map(self, len: Option<usize>) -> Result<Self::SerializeMap> {
-        self.serializer.serialize_map(len)
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        self.formatter
+            .begin_object(&mut self.writer)
+            .map_err(|error| self.fail(Error::Io(error)))?;
+        self.depth += 1;
+        Ok(SerializeMap {
+            ser: self,
+            first: true,
+            variant: None,
+            raw: false,
+        })
     }
 
+    /// Raw values are parsed strictly and written in canonical form in their place.
     #[inline]
     fn serialize_struct(self, name: &'static str, len: usize) -> Result<Self::SerializeStruct> {
-        self.serializer.serialize_struct(name, len)
+        if name == RAW_VALUE_TOKEN {
+            return Ok(SerializeMap {
+                ser: self,
+                first: true,
+                variant: None,
+                raw: true,
+            });
+        }
+        self.serialize_map(Some(len))
     }
 
+    /// Serialize struct variants as `{"variant":{...}}`.
     #[inline]
     fn serialize_struct_variant(
         self,
-        name: &'static str,
-        variant_index: u32,
+        _name: &'static str,
+        _variant_index: u32,
         variant: &'static str,
         len: usize,
     ) -> Result<Self::SerializeStructVariant> {
-        self.serializer
-            .serialize_struct_variant(name, variant_index, variant, len)
+        if let Err(error) = self.begin_variant(variant) {
+            return Err(self.fail(error));
+        }
+        let mut map = self.serialize_map(Some(len))?;
+        map.variant = Some(variant);
+        Ok(map)
+    }
+}
+
+impl<W: io::Write> JcsSerializer<W> {
+    /// Writes an integer that might not be exactly representable as a double according to the
+    /// [IntegerPolicy].
+    fn serialize_integer<T, F>(&mut self, value: T, exact: bool, write: F) -> Result<()>
+    where
+        T: Display,
+        F: FnOnce(&mut JcsFormatter, &mut W) -> io::Result<()>,
+    {
+        match self.integer_policy {
+            IntegerPolicy::Error if !exact => Err(lossy_integer_error(value)),
+            IntegerPolicy::String if !exact => self.serialize_str(&value.to_string()),
+            _ => write(&mut self.formatter, &mut self.writer).map_err(Error::Io),
+        }
+    }
+
+    /// Opens the `{"variant":` wrapper of tuple and struct variants.
+    fn begin_variant(&mut self, variant: &'static str) -> Result<()> {
+        self.formatter
+            .begin_object(&mut self.writer)
+            .map_err(Error::Io)?;
+        self.formatter
+            .begin_object_key(&mut self.writer, true)
+            .map_err(Error::Io)?;
+        self.serialize_str(variant)?;
+        self.formatter
+            .end_object_key(&mut self.writer)
+            .map_err(Error::Io)?;
+        self.formatter
+            .begin_object_value(&mut self.writer)
+            .map_err(Error::Io)
+    }
+
+    /// Adds the key of the `{"variant":` wrapper to the path of an error inside of the variant.
+    fn variant_error(&mut self, variant: Option<&'static str>, error: Error) -> Error {
+        match variant {
+            Some(variant) => {
+                self.formatter.discard_property();
+                error.in_property(variant)
+            }
+            None => error,
+        }
+    }
+
+    /// Closes the `{"variant":` wrapper of tuple and struct variants.
+    fn end_variant(&mut self) -> Result<()> {
+        self.formatter
+            .end_object_value(&mut self.writer)
+            .map_err(Error::Io)?;
+        self.formatter
+            .end_object(&mut self.writer)
+            .map_err(Error::Io)
+    }
+
+    /// Drops the state of the failed value once the error leaves the outermost array or object,
+    /// so that the next value starts from scratch.
+    fn fail(&mut self, error: Error) -> Error {
+        if self.depth == 0 {
+            self.formatter.reset();
+        }
+        error
+    }
+
+    /// Leaves an array or object that failed.
+    fn abandon(&mut self, error: Error) -> Error {
+        self.depth -= 1;
+        self.fail(error)
+    }
+}
+
+/// Serializes the elements of JSON arrays for the [JcsSerializer].
+///
+/// Used for sequences, tuples, tuple structs and tuple variants.
+pub struct SerializeSeq<'a, W> {
+    ser: &'a mut JcsSerializer<W>,
+    len: usize,
+    variant: Option<&'static str>,
+}
+
+impl<W: io::Write> SerializeSeq<'_, W> {
+    fn element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.try_element(value)
+            .map_err(|error| self.ser.abandon(error))
+    }
+
+    fn try_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.ser
+            .formatter
+            .begin_array_value(&mut self.ser.writer, self.len == 0)
+            .map_err(Error::Io)?;
+        let index = self.len;
+        self.len += 1;
+        value.serialize(&mut *self.ser).map_err(|error| {
+            self.ser
+                .variant_error(self.variant, error.in_element(index))
+        })?;
+        self.ser
+            .formatter
+            .end_array_value(&mut self.ser.writer)
+            .map_err(Error::Io)
+    }
+
+    fn finish(self) -> Result<()> {
+        self.ser.depth -= 1;
+        let result = self
+            .ser
+            .formatter
+            .end_array(&mut self.ser.writer)
+            .map_err(Error::Io);
+        let result = match (result, self.variant) {
+            (Ok(()), Some(_)) => self.ser.end_variant(),
+            (result, _) => result,
+        };
+        result.map_err(|error| self.ser.fail(error))
+    }
+}
+
+impl<W: io::Write> ser::SerializeSeq for SerializeSeq<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: io::Write> ser::SerializeTuple for SerializeSeq<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_element<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: io::Write> ser::SerializeTupleStruct for SerializeSeq<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: io::Write> ser::SerializeTupleVariant for SerializeSeq<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.element(value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+/// Serializes the properties of JSON objects for the [JcsSerializer].
+///
+/// Used for maps, structs and struct variants. The properties are sorted as required by
+/// RFC 8785 when the object is finished, so they can be serialized in any order.
+pub struct SerializeMap<'a, W> {
+    ser: &'a mut JcsSerializer<W>,
+    first: bool,
+    variant: Option<&'static str>,
+    /// Serializing a [serde_json] raw value, which has the JSON text as its only field.
+    raw: bool,
+}
+
+impl<W: io::Write> SerializeMap<'_, W> {
+    /// Writes the canonical form of the JSON text of a raw value.
+    fn raw_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        let json = match serde_json::to_value(value) {
+            Ok(serde_json::Value::String(json)) => json,
+            _ => return Err(raw_value_error()),
+        };
+        strict_value(json.as_bytes())?.serialize(&mut *self.ser)
+    }
+
+    fn finish(self) -> Result<()> {
+        if self.raw {
+            return Ok(());
+        }
+        self.ser.depth -= 1;
+        let result = self
+            .ser
+            .formatter
+            .end_object(&mut self.ser.writer)
+            .map_err(Error::Io);
+        let result = match (result, self.variant) {
+            (Ok(()), Some(_)) => self.ser.end_variant(),
+            (result, _) => result,
+        };
+        result.map_err(|error| self.ser.fail(error))
+    }
+
+    fn try_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.ser
+            .formatter
+            .begin_object_key(&mut self.ser.writer, self.first)
+            .map_err(Error::Io)?;
+        self.first = false;
+        key.serialize(MapKeySerializer { ser: self.ser })
+            .map_err(|error| {
+                self.ser.formatter.discard_property();
+                self.ser.variant_error(self.variant, error)
+            })?;
+        self.ser
+            .formatter
+            .end_object_key(&mut self.ser.writer)
+            .map_err(Error::Io)
+    }
+
+    fn try_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.ser
+            .formatter
+            .begin_object_value(&mut self.ser.writer)
+            .map_err(Error::Io)?;
+        value.serialize(&mut *self.ser).map_err(|error| {
+            let error = match self.ser.formatter.fail_property() {
+                Some(key) => error.in_property(&key),
+                None => error,
+            };
+            self.ser.variant_error(self.variant, error)
+        })?;
+        self.ser
+            .formatter
+            .end_object_value(&mut self.ser.writer)
+            .map_err(Error::Io)
+    }
+}
+
+impl<W: io::Write> SerializeMapTrait for SerializeMap<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_key<T>(&mut self, key: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.try_key(key).map_err(|error| self.ser.abandon(error))
+    }
+
+    #[inline]
+    fn serialize_value<T>(&mut self, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.try_value(value)
+            .map_err(|error| self.ser.abandon(error))
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: io::Write> ser::SerializeStruct for SerializeMap<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        if self.raw {
+            return self.raw_value(value);
+        }
+        self.serialize_entry(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+impl<W: io::Write> ser::SerializeStructVariant for SerializeMap<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    #[inline]
+    fn serialize_field<T>(&mut self, key: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        self.serialize_entry(key, value)
+    }
+
+    #[inline]
+    fn end(self) -> Result<()> {
+        self.finish()
+    }
+}
+
+fn key_must_be_a_string() -> Error {
+    Error::NonStringKey {
+        path: String::new(),
+    }
+}
+
+/// Serializes object keys, which must be strings in JSON.
+///
+/// Follows [serde_json] and accepts numbers and booleans as keys by writing them into a string.
+struct MapKeySerializer<'a, W> {
+    ser: &'a mut JcsSerializer<W>,
+}
+
+impl<W: io::Write> MapKeySerializer<'_, W> {
+    /// Writes a non-string key wrapped into a string.
+    fn quoted<F>(self, write: F) -> Result<()>
+    where
+        F: FnOnce(&mut JcsFormatter, &mut W) -> io::Result<()>,
+    {
+        let JcsSerializer {
+            writer, formatter, ..
+        } = self.ser;
+        formatter.begin_string(writer).map_err(Error::Io)?;
+        write(formatter, writer).map_err(Error::Io)?;
+        formatter.end_string(writer).map_err(Error::Io)
+    }
+
+    /// Writes an integer key according to the [IntegerPolicy]. Keys are strings anyway, so the
+    /// string policy writes the exact digits into the key.
+    fn quoted_integer<T, F>(self, value: T, exact: bool, write: F) -> Result<()>
+    where
+        T: Display,
+        F: FnOnce(&mut JcsFormatter, &mut W) -> io::Result<()>,
+    {
+        match self.ser.integer_policy {
+            IntegerPolicy::Error if !exact => Err(lossy_integer_error(value)),
+            IntegerPolicy::String if !exact => self.quoted(|formatter, writer| {
+                formatter.write_string_fragment(writer, &value.to_string())
+            }),
+            _ => self.quoted(write),
+        }
+    }
+}
+
+impl<W: io::Write> SerSerializer for MapKeySerializer<'_, W> {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = Impossible<(), Error>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    #[inline]
+    fn serialize_str(self, value: &str) -> Result<()> {
+        self.ser.serialize_str(value)
+    }
+
+    #[inline]
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<()> {
+        self.ser.serialize_str(variant)
+    }
+
+    #[inline]
+    fn serialize_newtype_struct<T>(self, _name: &'static str, value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        value.serialize(self)
+    }
+
+    fn serialize_bool(self, value: bool) -> Result<()> {
+        self.quoted(|formatter, writer| formatter.write_bool(writer, value))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<()> {
+        self.quoted(|formatter, writer| formatter.write_i8(writer, value))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<()> {
+        self.quoted(|formatter, writer| formatter.write_i16(writer, value))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<()> {
+        self.quoted(|formatter, writer| formatter.write_i32(writer, value))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<()> {
+        let exact = u128::from(value.unsigned_abs()) <= MAX_SAFE_INTEGER;
+        self.quoted_integer(value, exact, |formatter, writer| {
+            formatter.write_i64(writer, value)
+        })
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<()> {
+        let exact = value.unsigned_abs() <= MAX_SAFE_INTEGER;
+        self.quoted_integer(value, exact, |formatter, writer| {
+            formatter.write_i128(writer, value)
+        })
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<()> {
+        self.quoted(|formatter, writer| formatter.write_u8(writer, value))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<()> {
+        self.quoted(|formatter, writer| formatter.write_u16(writer, value))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<()> {
+        self.quoted(|formatter, writer| formatter.write_u32(writer, value))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<()> {
+        let exact = u128::from(value) <= MAX_SAFE_INTEGER;
+        self.quoted_integer(value, exact, |formatter, writer| {
+            formatter.write_u64(writer, value)
+        })
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<()> {
+        let exact = value <= MAX_SAFE_INTEGER;
+        self.quoted_integer(value, exact, |formatter, writer| {
+            formatter.write_u128(writer, value)
+        })
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<()> {
+        if !value.is_finite() {
+            return Err(non_finite_error());
+        }
+        self.quoted(|formatter, writer| formatter.write_f32(writer, value))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<()> {
+        if !value.is_finite() {
+            return Err(non_finite_error());
+        }
+        self.quoted(|formatter, writer| formatter.write_f64(writer, value))
+    }
+
+    #[inline]
+    fn serialize_char(self, value: char) -> Result<()> {
+        self.ser.serialize_char(value)
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<()> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit(self) -> Result<()> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<()> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_newtype_variant<T>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_none(self) -> Result<()> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_some<T>(self, _value: &T) -> Result<()>
+    where
+        T: ?Sized + Serialize,
+    {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self::SerializeStruct> {
+        Err(key_must_be_a_string())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(key_must_be_a_string())
     }
 
     fn collect_str<T>(self, value: &T) -> Result<()>
     where
         T: ?Sized + Display,
     {
-        self.serializer.collect_str(value)
+        self.ser.collect_str(value)
     }
 }
@@ -0,0 +1,376 @@
+//! JSON Signature Format (JSF) signatures.
+//!
+//! JSF embeds the signature into the signed document as its `signature` property. The signed data
+//! is the JCS form of the document including the signature object, with only the `value` of the
+//! signature being created or verified removed. Besides single signatures, JSF defines multiple
+//! independent signatures (`signers`) and signature chains (`chain`), where every signature also
+//! covers the ones before it.
+//!
+//! The public key is embedded as a JWK into each signature, it is up to the application to decide
+//! whether it trusts the keys returned by [verify].
+
+use std::fmt::Display;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ed25519_dalek::{Signer as _, Verifier as _};
+use serde_json::{json, Map, Value};
+
+use crate::{json_error::json_error, to_vec};
+
+const SIGNATURE: &str = "signature";
+const SIGNERS: &str = "signers";
+const CHAIN: &str = "chain";
+
+/// Error signing or verifying a JSF signature.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The document could not be canonicalized.
+    Json(crate::Error),
+    /// The document is not a JSON object.
+    NotAnObject,
+    /// The private key, or the embedded public key, is not valid for the algorithm.
+    InvalidKey,
+    /// The algorithm of the signature is not supported.
+    UnsupportedAlgorithm(String),
+    /// The signature object is malformed, the message describes why.
+    InvalidSignature(&'static str),
+    /// The signature does not match the document.
+    VerificationFailed,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Json(error) => write!(f, "{error}"),
+            Error::NotAnObject => f.write_str("document is not a JSON object"),
+            Error::InvalidKey => f.write_str("key is not valid for the algorithm"),
+            Error::UnsupportedAlgorithm(algorithm) => {
+                write!(f, "unsupported signature algorithm `{algorithm}`")
+            }
+            Error::InvalidSignature(reason) => write!(f, "invalid signature: {reason}"),
+            Error::VerificationFailed => f.write_str("signature verification failed"),
+        }
+    }
+}
+
+json_error!(Error);
+
+/// Signature algorithms, named as in JSF.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Algorithm {
+    /// EdDSA with Ed25519, the key is the 32 byte secret key.
+    Ed25519,
+    /// ECDSA with P-256 and SHA-256, the key is the 32 byte secret scalar.
+    ES256,
+}
+
+impl Algorithm {
+    fn name(self) -> &'static str {
+        match self {
+            Algorithm::Ed25519 => "Ed25519",
+            Algorithm::ES256 => "ES256",
+        }
+    }
+
+    fn from_name(name: &str) -> Result<Self, Error> {
+        match name {
+            "Ed25519" => Ok(Algorithm::Ed25519),
+            "ES256" => Ok(Algorithm::ES256),
+            _ => Err(Error::UnsupportedAlgorithm(name.to_owned())),
+        }
+    }
+}
+
+enum PrivateKey {
+    Ed25519(ed25519_dalek::SigningKey),
+    ES256(p256::ecdsa::SigningKey),
+}
+
+impl PrivateKey {
+    fn new(key: &[u8], algorithm: Algorithm) -> Result<Self, Error> {
+        match algorithm {
+            Algorithm::Ed25519 => key
+                .try_into()
+                .map(|key| PrivateKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(key)))
+                .map_err(|_| Error::InvalidKey),
+            Algorithm::ES256 => p256::ecdsa::SigningKey::from_slice(key)
+                .map(PrivateKey::ES256)
+                .map_err(|_| Error::InvalidKey),
+        }
+    }
+
+    /// The signature object without its value.
+    fn signer(&self) -> Value {
+        let (algorithm, public_key) = match self {
+            PrivateKey::Ed25519(key) => (
+                Algorithm::Ed25519,
+                json!({
+                    "kty": "OKP",
+                    "crv": "Ed25519",
+                    "x": URL_SAFE_NO_PAD.encode(key.verifying_key().as_bytes()),
+                }),
+            ),
+            PrivateKey::ES256(key) => {
+                let point = key.verifying_key().to_encoded_point(false);
+                (
+                    Algorithm::ES256,
+                    json!({
+                        "kty": "EC",
+                        "crv": "P-256",
+                        "x": point.x().map(|x| URL_SAFE_NO_PAD.encode(x)),
+                        "y": point.y().map(|y| URL_SAFE_NO_PAD.encode(y)),
+                    }),
+                )
+            }
+        };
+        json!({"algorithm": algorithm.name(), "publicKey": public_key})
+    }
+
+    fn sign(&self, data: &[u8]) -> String {
+        let signature = match self {
+            PrivateKey::Ed25519(key) => key.sign(data).to_vec(),
+            PrivateKey::ES256(key) => {
+                let signature: p256::ecdsa::Signature = key.sign(data);
+                signature.to_vec()
+            }
+        };
+        URL_SAFE_NO_PAD.encode(signature)
+    }
+}
+
+enum PublicKey {
+    Ed25519(ed25519_dalek::VerifyingKey),
+    ES256(p256::ecdsa::VerifyingKey),
+}
+
+impl PublicKey {
+    fn from_jwk(jwk: &Value, algorithm: Algorithm) -> Result<Self, Error> {
+        let member = |name: &str| -> Result<[u8; 32], Error> {
+            jwk.get(name)
+                .and_then(Value::as_str)
+                .and_then(|encoded| URL_SAFE_NO_PAD.decode(encoded).ok())
+                .and_then(|decoded| decoded.try_into().ok())
+                .ok_or(Error::InvalidKey)
+        };
+        let kty = jwk.get("kty").and_then(Value::as_str);
+        let crv = jwk.get("crv").and_then(Value::as_str);
+        match (algorithm, kty, crv) {
+            (Algorithm::Ed25519, Some("OKP"), Some("Ed25519")) => {
+                ed25519_dalek::VerifyingKey::from_bytes(&member("x")?)
+                    .map(PublicKey::Ed25519)
+                    .map_err(|_| Error::InvalidKey)
+            }
+            (Algorithm::ES256, Some("EC"), Some("P-256")) => {
+                let point = p256::EncodedPoint::from_affine_coordinates(
+                    &member("x")?.into(),
+                    &member("y")?.into(),
+                    false,
+                );
+                p256::ecdsa::VerifyingKey::from_encoded_point(&point)
+                    .map(PublicKey::ES256)
+                    .map_err(|_| Error::InvalidKey)
+            }
+            _ => Err(Error::InvalidKey),
+        }
+    }
+
+    fn verify(&self, data: &[u8], signature: &[u8]) -> Result<(), Error> {
+        let verified = match self {
+            PublicKey::Ed25519(key) => ed25519_dalek::Signature::from_slice(signature)
+                .and_then(|signature| key.verify(data, &signature)),
+            PublicKey::ES256(key) => p256::ecdsa::Signature::from_slice(signature)
+                .and_then(|signature| key.verify(data, &signature)),
+        };
+        verified.map_err(|_| Error::VerificationFailed)
+    }
+}
+
+/// Sign the document with a single signature.
+///
+/// The signature, including the public key as a JWK, is added to the document as its `signature`
+/// property.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::jsf::{sign, verify, Algorithm};
+///
+/// let mut document = serde_json::json!({"now": "2019-02-10T11:23:06Z", "escapeMe": "\u{20ac}$"});
+/// sign(&mut document, &[7; 32], Algorithm::Ed25519).unwrap();
+///
+/// assert_eq!(document["signature"]["algorithm"], "Ed25519");
+/// assert_eq!(verify(&document).unwrap(), [&document["signature"]["publicKey"]]);
+/// ```
+///
+/// # Errors
+///
+/// Fails if the document is not an object or is already signed, or the key is not valid for the
+/// algorithm.
+pub fn sign(document: &mut Value, key: &[u8], algorithm: Algorithm) -> Result<(), Error> {
+    let key = PrivateKey::new(key, algorithm)?;
+    let object = document.as_object_mut().ok_or(Error::NotAnObject)?;
+    if object.contains_key(SIGNATURE) {
+        return Err(Error::InvalidSignature("document is already signed"));
+    }
+    object.insert(SIGNATURE.to_owned(), key.signer());
+
+    let value = key.sign(&to_vec(object)?);
+    object[SIGNATURE]["value"] = value.into();
+    Ok(())
+}
+
+/// Add an independent signature to the `signers` of a multi-signature.
+///
+/// Each signature of a multi-signature covers the document with only the signature itself in the
+/// `signers` array, so they can be added in any order.
+///
+/// # Errors
+///
+/// Fails if the document is not an object or has a signature that is not a multi-signature, or
+/// the key is not valid for the algorithm.
+pub fn sign_multi(document: &mut Value, key: &[u8], algorithm: Algorithm) -> Result<(), Error> {
+    add_signer(document, key, algorithm, SIGNERS)
+}
+
+/// Append a signature to a signature chain.
+///
+/// Each signature of a chain covers the document with all the previous signatures of the `chain`
+/// array, so none of them can be removed or reordered without invalidating the later ones.
+///
+/// # Errors
+///
+/// Fails if the document is not an object or has a signature that is not a signature chain, or
+/// the key is not valid for the algorithm.
+pub fn sign_chain(document: &mut Value, key: &[u8], algorithm: Algorithm) -> Result<(), Error> {
+    add_signer(document, key, algorithm, CHAIN)
+}
+
+fn add_signer(
+    document: &mut Value,
+    key: &[u8],
+    algorithm: Algorithm,
+    array: &'static str,
+) -> Result<(), Error> {
+    let key = PrivateKey::new(key, algorithm)?;
+    let object = document.as_object_mut().ok_or(Error::NotAnObject)?;
+    let signers = object
+        .entry(SIGNATURE)
+        .or_insert_with(|| json!({ array: [] }))
+        .get_mut(array)
+        .and_then(Value::as_array_mut)
+        .ok_or(Error::InvalidSignature(match array {
+            SIGNERS => "document has a signature that is not a multi-signature",
+            _ => "document has a signature that is not a signature chain",
+        }))?;
+    let previous = match array {
+        SIGNERS => std::mem::take(signers),
+        _ => Vec::new(),
+    };
+    signers.push(key.signer());
+
+    let value = key.sign(&to_vec(object)?);
+    let signers = object[SIGNATURE][array]
+        .as_array_mut()
+        .expect("signers were added above");
+    let signer = signers.pop().map(|mut signer| {
+        signer["value"] = value.into();
+        signer
+    });
+    signers.splice(0..0, previous);
+    signers.extend(signer);
+    Ok(())
+}
+
+/// Verifies one signature, `embed` returns the document that the signature without its value is
+/// part of. Returns the public key of the signature.
+fn verify_signer(
+    signer: &Value,
+    embed: impl FnOnce(Value) -> Map<String, Value>,
+) -> Result<&Value, Error> {
+    let signer_object = signer
+        .as_object()
+        .ok_or(Error::InvalidSignature("signature is not an object"))?;
+    let algorithm = signer_object
+        .get("algorithm")
+        .and_then(Value::as_str)
+        .ok_or(Error::InvalidSignature("signature has no algorithm"))
+        .and_then(Algorithm::from_name)?;
+    let jwk = signer_object
+        .get("publicKey")
+        .ok_or(Error::InvalidSignature("signature has no publicKey"))?;
+    let public_key = PublicKey::from_jwk(jwk, algorithm)?;
+    let signature = signer_object
+        .get("value")
+        .and_then(Value::as_str)
+        .and_then(|value| URL_SAFE_NO_PAD.decode(value).ok())
+        .ok_or(Error::InvalidSignature("value is missing or not base64url"))?;
+
+    let mut unsigned = signer_object.clone();
+    unsigned.remove("value");
+    let signed_data = to_vec(&embed(Value::Object(unsigned)))?;
+    public_key.verify(&signed_data, &signature)?;
+    Ok(jwk)
+}
+
+/// Verify the signatures of the document.
+///
+/// Verifies a single signature, every signature of a multi-signature, or every signature of a
+/// chain. Returns the public keys of the signatures in the order they appear in the document, the
+/// application has to check that these belong to the expected signers.
+///
+/// # Errors
+///
+/// Fails with [Error::VerificationFailed] if any of the signatures does not match, or with another
+/// error if the document is not signed or a signature is malformed or uses an unsupported
+/// algorithm. Signatures using the `excludes` option are not supported.
+pub fn verify(document: &Value) -> Result<Vec<&Value>, Error> {
+    let object = document.as_object().ok_or(Error::NotAnObject)?;
+    let signature = object
+        .get(SIGNATURE)
+        .ok_or(Error::InvalidSignature("document is not signed"))?
+        .as_object()
+        .ok_or(Error::InvalidSignature("signature is not an object"))?;
+    if signature.contains_key("excludes") {
+        return Err(Error::InvalidSignature("excludes is not supported"));
+    }
+    let with_signature = |signature: Value| {
+        let mut document = object.clone();
+        document.insert(SIGNATURE.to_owned(), signature);
+        document
+    };
+    let with_signers = |array: &str, signers: Vec<Value>| {
+        let mut signature = signature.clone();
+        signature.insert(array.to_owned(), Value::Array(signers));
+        with_signature(Value::Object(signature))
+    };
+
+    if let Some(signers) = signature.get(SIGNERS) {
+        let signers = signers
+            .as_array()
+            .filter(|signers| !signers.is_empty())
+            .ok_or(Error::InvalidSignature("signers is not a non-empty array"))?;
+        signers
+            .iter()
+            .map(|signer| verify_signer(signer, |unsigned| with_signers(SIGNERS, vec![unsigned])))
+            .collect()
+    } else if let Some(chain) = signature.get(CHAIN) {
+        let chain = chain
+            .as_array()
+            .filter(|chain| !chain.is_empty())
+            .ok_or(Error::InvalidSignature("chain is not a non-empty array"))?;
+        chain
+            .iter()
+            .enumerate()
+            .map(|(index, signer)| {
+                verify_signer(signer, |unsigned| {
+                    let mut signers = chain[..index].to_vec();
+                    signers.push(unsigned);
+                    with_signers(CHAIN, signers)
+                })
+            })
+            .collect()
+    } else {
+        verify_signer(&object[SIGNATURE], with_signature).map(|jwk| vec![jwk])
+    }
+}
@@ -0,0 +1,32 @@
+//! The error plumbing shared by the [jsf](crate::jsf), [jwk](crate::jwk), [jws](crate::jws) and
+//! [data_integrity](crate::data_integrity) modules, whose errors wrap the crate
+//! [Error](crate::Error) in a `Json` variant.
+
+/// Implements `std::error::Error` with the `Json` variant as source, and the conversions from the
+/// crate and `serde_json` errors into that variant, for the given error type.
+macro_rules! json_error {
+    ($error:ident) => {
+        impl std::error::Error for $error {
+            fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+                match self {
+                    $error::Json(error) => Some(error),
+                    _ => None,
+                }
+            }
+        }
+
+        impl From<crate::Error> for $error {
+            fn from(error: crate::Error) -> Self {
+                $error::Json(error)
+            }
+        }
+
+        impl From<serde_json::Error> for $error {
+            fn from(error: serde_json::Error) -> Self {
+                $error::Json(error.into())
+            }
+        }
+    };
+}
+
+pub(crate) use json_error;
@@ -0,0 +1,129 @@
+//! JSON Web Key (JWK) Thumbprints (RFC 7638) and their URI form (RFC 9278).
+//!
+//! The thumbprint is the SHA-256 hash of the JCS serialization of the required members of the key,
+//! encoded as base64url without padding.
+
+use std::fmt::Display;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sha2::Sha256;
+
+use crate::{json_error::json_error, to_digest};
+
+/// URI prefix of SHA-256 JWK Thumbprints defined by RFC 9278.
+const THUMBPRINT_URI_PREFIX: &str = "urn:ietf:params:oauth:jwk-thumbprint:sha-256:";
+
+/// Error computing a JWK Thumbprint.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The key could not be serialized as JSON.
+    Json(crate::Error),
+    /// The key is not a JSON object.
+    NotAnObject,
+    /// The `kty` member is not one of `RSA`, `EC`, `OKP` or `oct`.
+    UnsupportedKeyType(String),
+    /// A member required for the key type is missing or is not a string.
+    MissingMember(&'static str),
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Json(error) => write!(f, "{error}"),
+            Error::NotAnObject => f.write_str("JWK is not a JSON object"),
+            Error::UnsupportedKeyType(kty) => write!(f, "unsupported JWK key type `{kty}`"),
+            Error::MissingMember(member) => {
+                write!(
+                    f,
+                    "required JWK member `{member}` is missing or not a string"
+                )
+            }
+        }
+    }
+}
+
+json_error!(Error);
+
+/// Selects the members of the key that the thumbprint is computed from.
+///
+/// These are listed in RFC 7638 for `RSA`, `EC` and `oct` keys, and in RFC 8037 for `OKP` keys.
+fn required_members(jwk: &impl Serialize) -> Result<Value, Error> {
+    let jwk = serde_json::to_value(jwk)?;
+    let jwk = jwk.as_object().ok_or(Error::NotAnObject)?;
+    let kty = jwk
+        .get("kty")
+        .and_then(Value::as_str)
+        .ok_or(Error::MissingMember("kty"))?;
+    let members: &[&'static str] = match kty {
+        "RSA" => &["e", "kty", "n"],
+        "EC" => &["crv", "kty", "x", "y"],
+        "OKP" => &["crv", "kty", "x"],
+        "oct" => &["k", "kty"],
+        _ => return Err(Error::UnsupportedKeyType(kty.to_owned())),
+    };
+    members
+        .iter()
+        .map(|&member| match jwk.get(member) {
+            Some(value @ Value::String(_)) => Ok((member.to_owned(), value.clone())),
+            _ => Err(Error::MissingMember(member)),
+        })
+        .collect::<Result<Map<_, _>, _>>()
+        .map(Value::Object)
+}
+
+/// Compute the base64url encoded SHA-256 JWK Thumbprint of the key.
+///
+/// The key can be a [serde_json::Value] or any type that serializes into a JWK. Members that are
+/// not required for the key type, like `kid` or `use`, are ignored.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::jwk::thumbprint;
+///
+/// let jwk = serde_json::json!({
+///     "kty": "OKP",
+///     "crv": "Ed25519",
+///     "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo",
+///     "kid": "ignored",
+/// });
+///
+/// assert_eq!(thumbprint(&jwk).unwrap(), "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k");
+/// ```
+///
+/// # Errors
+///
+/// Fails if the key cannot be serialized, is not an object, has an unsupported key type, or a
+/// required member is missing.
+pub fn thumbprint(jwk: &impl Serialize) -> Result<String, Error> {
+    let hash = to_digest::<Sha256>(&required_members(jwk)?)?;
+    Ok(URL_SAFE_NO_PAD.encode(hash))
+}
+
+/// Compute the JWK Thumbprint URI (RFC 9278) of the key.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::jwk::thumbprint_uri;
+///
+/// let jwk = serde_json::json!({
+///     "kty": "OKP",
+///     "crv": "Ed25519",
+///     "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo",
+/// });
+///
+/// assert_eq!(
+///     thumbprint_uri(&jwk).unwrap(),
+///     "urn:ietf:params:oauth:jwk-thumbprint:sha-256:kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k"
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Fails if the key cannot be serialized, is not an object, has an unsupported key type, or a
+/// required member is missing.
+pub fn thumbprint_uri(jwk: &impl Serialize) -> Result<String, Error> {
+    Ok(format!("{THUMBPRINT_URI_PREFIX}{}", thumbprint(jwk)?))
+}
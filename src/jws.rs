@@ -0,0 +1,221 @@
+//! Detached JSON Web Signatures (JWS) over the JCS form of a payload.
+//!
+//! The signatures use the unencoded payload option of RFC 7797: the header is
+//! `{"alg":…,"b64":false,"crit":["b64"]}` and the JCS bytes of the payload are signed as they are.
+//! The payload is left out of the compact serialization (`header..signature`), the verifier
+//! canonicalizes its own copy of the payload.
+
+use std::fmt::Display;
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ed25519_dalek::{Signer as _, Verifier as _};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::{json, Value};
+use sha2::Sha256;
+
+use crate::{json_error::json_error, to_vec};
+
+/// Error signing or verifying a detached JWS.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The payload or the header could not be serialized.
+    Json(crate::Error),
+    /// The JWS is not a detached compact JWS with an unencoded payload, the message describes why.
+    InvalidFormat(&'static str),
+    /// The `alg` of the header does not match the key.
+    AlgorithmMismatch,
+    /// The signature does not match the payload.
+    VerificationFailed,
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Error::Json(error) => write!(f, "{error}"),
+            Error::InvalidFormat(reason) => write!(f, "invalid detached JWS: {reason}"),
+            Error::AlgorithmMismatch => f.write_str("JWS algorithm does not match the key"),
+            Error::VerificationFailed => f.write_str("JWS verification failed"),
+        }
+    }
+}
+
+json_error!(Error);
+
+/// A key creating signatures.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum SigningKey {
+    /// Shared secret for HMAC with SHA-256.
+    HS256(Vec<u8>),
+    /// P-256 key for ECDSA with SHA-256.
+    ES256(p256::ecdsa::SigningKey),
+    /// Ed25519 key for EdDSA.
+    EdDSA(ed25519_dalek::SigningKey),
+}
+
+impl SigningKey {
+    fn alg(&self) -> &'static str {
+        match self {
+            SigningKey::HS256(_) => "HS256",
+            SigningKey::ES256(_) => "ES256",
+            SigningKey::EdDSA(_) => "EdDSA",
+        }
+    }
+
+    fn sign(&self, signing_input: &[u8]) -> Vec<u8> {
+        match self {
+            SigningKey::HS256(secret) => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+                    .expect("HMAC accepts keys of any length");
+                mac.update(signing_input);
+                mac.finalize().into_bytes().to_vec()
+            }
+            SigningKey::ES256(key) => {
+                let signature: p256::ecdsa::Signature = key.sign(signing_input);
+                signature.to_vec()
+            }
+            SigningKey::EdDSA(key) => key.sign(signing_input).to_vec(),
+        }
+    }
+}
+
+/// A key verifying signatures.
+#[derive(Clone, Debug)]
+#[non_exhaustive]
+pub enum VerifyingKey {
+    /// Shared secret for HMAC with SHA-256.
+    HS256(Vec<u8>),
+    /// P-256 key for ECDSA with SHA-256.
+    ES256(p256::ecdsa::VerifyingKey),
+    /// Ed25519 key for EdDSA.
+    EdDSA(ed25519_dalek::VerifyingKey),
+}
+
+impl VerifyingKey {
+    fn alg(&self) -> &'static str {
+        match self {
+            VerifyingKey::HS256(_) => "HS256",
+            VerifyingKey::ES256(_) => "ES256",
+            VerifyingKey::EdDSA(_) => "EdDSA",
+        }
+    }
+
+    fn verify(&self, signing_input: &[u8], signature: &[u8]) -> Result<(), Error> {
+        let verified = match self {
+            VerifyingKey::HS256(secret) => {
+                let mut mac = Hmac::<Sha256>::new_from_slice(secret)
+                    .expect("HMAC accepts keys of any length");
+                mac.update(signing_input);
+                return mac
+                    .verify_slice(signature)
+                    .map_err(|_| Error::VerificationFailed);
+            }
+            VerifyingKey::ES256(key) => p256::ecdsa::Signature::from_slice(signature)
+                .and_then(|signature| key.verify(signing_input, &signature)),
+            VerifyingKey::EdDSA(key) => ed25519_dalek::Signature::from_slice(signature)
+                .and_then(|signature| key.verify(signing_input, &signature)),
+        };
+        verified.map_err(|_| Error::VerificationFailed)
+    }
+}
+
+impl From<&SigningKey> for VerifyingKey {
+    fn from(key: &SigningKey) -> Self {
+        match key {
+            SigningKey::HS256(secret) => VerifyingKey::HS256(secret.clone()),
+            SigningKey::ES256(key) => VerifyingKey::ES256(*key.verifying_key()),
+            SigningKey::EdDSA(key) => VerifyingKey::EdDSA(key.verifying_key()),
+        }
+    }
+}
+
+/// The JWS Signing Input: the encoded header, a period and the unencoded payload.
+fn signing_input(encoded_header: &str, payload: &impl Serialize) -> Result<Vec<u8>, Error> {
+    let mut signing_input = encoded_header.as_bytes().to_vec();
+    signing_input.push(b'.');
+    signing_input.extend(to_vec(payload)?);
+    Ok(signing_input)
+}
+
+/// Checks the header of a detached JWS with an unencoded payload and returns its `alg`.
+fn check_header(encoded_header: &str) -> Result<String, Error> {
+    let header: Value = URL_SAFE_NO_PAD
+        .decode(encoded_header)
+        .ok()
+        .and_then(|header| serde_json::from_slice(&header).ok())
+        .ok_or(Error::InvalidFormat("header is not base64url encoded JSON"))?;
+    if header.get("b64") != Some(&Value::Bool(false)) {
+        return Err(Error::InvalidFormat(
+            "header does not have b64 set to false",
+        ));
+    }
+    match header.get("crit").and_then(Value::as_array) {
+        Some(crit) if !crit.is_empty() && crit.iter().all(|name| name == "b64") => {}
+        _ => {
+            return Err(Error::InvalidFormat(
+                "header crit must list b64 and no other extension",
+            ))
+        }
+    }
+    header
+        .get("alg")
+        .and_then(Value::as_str)
+        .map(str::to_owned)
+        .ok_or(Error::InvalidFormat("header has no alg"))
+}
+
+/// Sign the JCS form of the payload, returning a detached compact JWS.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::jws::{sign, verify, SigningKey};
+///
+/// let key = SigningKey::HS256(b"secret".to_vec());
+/// let payload = serde_json::json!({"b": 2, "a": 1});
+/// let jws = sign(&payload, &key).unwrap();
+///
+/// assert!(jws.starts_with("eyJhbGciOiJIUzI1NiIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19.."));
+/// verify(&jws, &serde_json::json!({"a": 1, "b": 2}), &(&key).into()).unwrap();
+/// ```
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys.
+pub fn sign(payload: &impl Serialize, key: &SigningKey) -> Result<String, Error> {
+    let header = to_vec(&json!({"alg": key.alg(), "b64": false, "crit": ["b64"]}))?;
+    let encoded_header = URL_SAFE_NO_PAD.encode(header);
+    let signature = key.sign(&signing_input(&encoded_header, payload)?);
+    Ok(format!(
+        "{encoded_header}..{}",
+        URL_SAFE_NO_PAD.encode(signature)
+    ))
+}
+
+/// Verify a detached compact JWS against the JCS form of the payload.
+///
+/// # Errors
+///
+/// Fails with [Error::VerificationFailed] if the signature does not match, or with another error
+/// if the JWS is malformed, its payload is not detached and unencoded, or its `alg` does not match
+/// the key.
+pub fn verify(jws: &str, payload: &impl Serialize, key: &VerifyingKey) -> Result<(), Error> {
+    let mut parts = jws.split('.');
+    let (Some(encoded_header), Some(""), Some(encoded_signature), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return Err(Error::InvalidFormat(
+            "not a compact JWS with a detached payload",
+        ));
+    };
+    if check_header(encoded_header)? != key.alg() {
+        return Err(Error::AlgorithmMismatch);
+    }
+    let signature = URL_SAFE_NO_PAD
+        .decode(encoded_signature)
+        .map_err(|_| Error::InvalidFormat("signature is not base64url encoded"))?;
+
+    key.verify(&signing_input(encoded_header, payload)?, &signature)
+}
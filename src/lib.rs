@@ -1,8 +1,83 @@
 #![doc = include_str!("../README.md")]
 #![warn(missing_docs)]
+#![cfg_attr(not(feature = "std"), no_std)]
 
+#[cfg(not(feature = "alloc"))]
+compile_error!(
+    "serde_json_canonicalizer requires either the `std` (default) or the `alloc` feature"
+);
+
+extern crate alloc;
+
+#[cfg(feature = "base64")]
+pub mod as_bytes;
+pub mod as_string;
+#[cfg(feature = "async")]
+mod async_io;
+mod canonical;
+mod canonicalizer;
+#[cfg(feature = "data-integrity")]
+pub mod data_integrity;
+mod error;
+#[cfg(feature = "async")]
+pub mod futures;
+#[cfg(feature = "digest")]
+mod hash;
+#[cfg(all(feature = "std", feature = "digest"))]
+mod hex;
+pub mod io;
 mod jcs;
+#[cfg(feature = "jsf")]
+pub mod jsf;
+#[cfg(any(
+    feature = "data-integrity",
+    feature = "jsf",
+    feature = "jwk",
+    feature = "jws"
+))]
+mod json_error;
+#[cfg(feature = "jwk")]
+pub mod jwk;
+#[cfg(feature = "jws")]
+pub mod jws;
+#[cfg(feature = "std")]
+mod lines;
+#[cfg(feature = "rayon")]
+mod parallel;
+#[cfg(feature = "std")]
+mod stream;
+mod strict;
+#[cfg(feature = "async")]
+pub mod tokio;
 mod util;
+mod value;
+mod verify;
 
+#[doc(inline)]
+pub use crate::canonical::{Canonical, CanonicalValue};
+#[doc(inline)]
+pub use crate::canonicalizer::Canonicalizer;
+#[doc(inline)]
+pub use crate::error::Error;
+#[cfg(feature = "digest")]
+#[doc(inline)]
+pub use crate::hash::{to_digest, to_hasher, HashWriter};
+#[doc(inline)]
+pub use crate::jcs::{IntegerPolicy, JcsSerializer, SerializeMap, SerializeSeq};
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use crate::lines::{pipe_lines, LineError, LinePipe};
+#[cfg(feature = "rayon")]
+#[doc(inline)]
+pub use crate::parallel::{canonicalize_batch, par_to_vec};
+#[cfg(feature = "std")]
+#[doc(inline)]
+pub use crate::stream::pipe_reader;
+#[doc(inline)]
+pub use crate::strict::{from_slice_strict, from_str_strict};
 #[doc(inline)]
 pub use crate::util::*;
+#[doc(inline)]
+pub use crate::value::{value_to_vec, value_to_vec_with};
+#[doc(inline)]
+pub use crate::verify::{is_canonical, verify_canonical, Deviation, NotCanonical};
@@ -0,0 +1,202 @@
+//! Canonicalizing JSON Lines (NDJSON) record by record.
+//!
+//! Every line of the input holds a JSON document, which is canonicalized on its own and written as
+//! one line of the output, so line `n` of the output always belongs to line `n` of the input.
+
+use std::{
+    fmt,
+    io::{BufRead, Write},
+};
+
+use serde::ser::StdError;
+
+use crate::{pipe_reader, Error};
+
+/// Canonicalize every line of JSON Lines input on its own, writing each record as one line.
+///
+/// Empty and whitespace-only lines are written as empty lines. Lines keep their `\n` or `\r\n`
+/// line break, and the output ends with a line break only if the input does. Of duplicate keys the
+/// last one is kept, as with [pipe](crate::pipe). Use a [LinePipe] to skip invalid lines or to
+/// prefix the records with their hash.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::pipe_lines;
+///
+/// let input = "{\"b\": 2, \"a\": 1}\n[1.0, \"x\"]\n";
+/// let mut output = Vec::new();
+/// pipe_lines(input.as_bytes(), &mut output).unwrap();
+///
+/// assert_eq!(output, b"{\"a\":1,\"b\":2}\n[1,\"x\"]\n");
+/// ```
+///
+/// # Errors
+///
+/// Stops at the first line that is not valid JSON or that has numbers out of range, or if reading
+/// or writing fails. The lines before it are written out.
+pub fn pipe_lines<R, W>(reader: R, writer: W) -> Result<(), LineError>
+where
+    R: BufRead,
+    W: Write,
+{
+    LinePipe::new().pipe(reader, writer).map(|_| ())
+}
+
+/// Options for canonicalizing JSON Lines, see [pipe_lines].
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::LinePipe;
+///
+/// let input = "{\"b\": 2, \"a\": 1}\n{\"a\":\n[1.0]\n";
+/// let mut output = Vec::new();
+/// let errors = LinePipe::new()
+///     .skip_invalid(true)
+///     .pipe(input.as_bytes(), &mut output)
+///     .unwrap();
+///
+/// assert_eq!(output, b"{\"a\":1,\"b\":2}\n\n[1]\n");
+/// assert_eq!(errors.len(), 1);
+/// assert_eq!(errors[0].line(), 2);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LinePipe {
+    skip_invalid: bool,
+    hash: Option<fn(&[u8]) -> String>,
+}
+
+impl LinePipe {
+    /// Creates options that stop at the first invalid line and write only the records.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets whether invalid lines are written as empty lines and collected instead of stopping.
+    pub fn skip_invalid(mut self, skip_invalid: bool) -> Self {
+        self.skip_invalid = skip_invalid;
+        self
+    }
+
+    /// Prefixes every record with the lowercase hex hash of its canonical form and a tab, as
+    /// `hash<TAB>canonical`.
+    ///
+    /// # Usage
+    /// ```
+    /// use serde_json_canonicalizer::LinePipe;
+    /// use sha2::Sha256;
+    ///
+    /// let mut output = Vec::new();
+    /// LinePipe::new()
+    ///     .with_digest::<Sha256>()
+    ///     .pipe(&b"{}\n"[..], &mut output)
+    ///     .unwrap();
+    ///
+    /// assert_eq!(
+    ///     output,
+    ///     b"44136fa355b3678a1146ad16f7e8649e94fb4fc21fe77e8310c060f61caaff8a\t{}\n"
+    /// );
+    /// ```
+    #[cfg(feature = "digest")]
+    pub fn with_digest<D: digest::Digest>(mut self) -> Self {
+        self.hash = Some(hex_digest::<D>);
+        self
+    }
+
+    /// Canonicalize every line of the reader into the writer.
+    ///
+    /// Returns the errors of the invalid lines that were skipped, which is always empty unless
+    /// [skip_invalid](Self::skip_invalid) is set.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reading or writing fails, or on the first invalid line unless invalid lines are
+    /// skipped. The lines before it are written out.
+    pub fn pipe<R, W>(&self, mut reader: R, mut writer: W) -> Result<Vec<LineError>, LineError>
+    where
+        R: BufRead,
+        W: Write,
+    {
+        let mut skipped = Vec::new();
+        let mut input = Vec::new();
+        let mut output = Vec::new();
+        let mut line = 0;
+        loop {
+            input.clear();
+            line += 1;
+            let read = reader.read_until(b'\n', &mut input);
+            let at_line = |error: Error| LineError { line, error };
+            if read.map_err(|error| at_line(error.into()))? == 0 {
+                return Ok(skipped);
+            }
+            let end = if input.ends_with(b"\r\n") {
+                2
+            } else {
+                usize::from(input.ends_with(b"\n"))
+            };
+            let (record, end) = input.split_at(input.len() - end);
+
+            output.clear();
+            if !record.iter().all(u8::is_ascii_whitespace) {
+                match pipe_reader(record, &mut output) {
+                    Ok(()) => {
+                        if let Some(hash) = self.hash {
+                            let mut hashed = hash(&output).into_bytes();
+                            hashed.push(b'\t');
+                            output.splice(0..0, hashed);
+                        }
+                    }
+                    Err(invalid) if self.skip_invalid => {
+                        skipped.push(at_line(invalid));
+                        output.clear();
+                    }
+                    Err(invalid) => return Err(at_line(invalid)),
+                }
+            }
+            output.extend_from_slice(end);
+            writer
+                .write_all(&output)
+                .map_err(|error| at_line(error.into()))?;
+        }
+    }
+}
+
+#[cfg(feature = "digest")]
+fn hex_digest<D: digest::Digest>(json: &[u8]) -> String {
+    crate::hex::encode(&D::digest(json))
+}
+
+/// Error of a line of JSON Lines input.
+#[derive(Debug)]
+pub struct LineError {
+    line: usize,
+    error: Error,
+}
+
+impl LineError {
+    /// The number of the line, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The error of the line.
+    pub fn error(&self) -> &Error {
+        &self.error
+    }
+
+    /// Consumes the error returning the error of the line.
+    pub fn into_error(self) -> Error {
+        self.error
+    }
+}
+
+impl fmt::Display for LineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+impl StdError for LineError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        Some(&self.error)
+    }
+}
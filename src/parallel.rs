@@ -0,0 +1,97 @@
+//! Canonicalizing the elements of large arrays and batches of values on multiple threads.
+//!
+//! Elements are serialized independently, each thread reusing the buffers of its own
+//! [Canonicalizer], and concatenated in order, so the output is byte-identical to the sequential
+//! serialization.
+
+use rayon::prelude::*;
+use serde::Serialize;
+
+use crate::{Canonicalizer, Error};
+
+/// Number of chunks per thread an array is split into, to balance elements of different sizes.
+const CHUNKS_PER_THREAD: usize = 4;
+
+/// Serialize a slice as a JCS array, canonicalizing its elements in parallel on the rayon thread
+/// pool.
+///
+/// The output is the same as the one of [to_vec](crate::to_vec) for the slice. Every thread
+/// serializes consecutive elements into its own buffer, which are then concatenated, so the
+/// elements should be large or numerous enough for this to pay off.
+///
+/// # Usage
+/// ```
+/// use serde_json::json;
+/// use serde_json_canonicalizer::{par_to_vec, to_vec};
+///
+/// let records: Vec<_> = (0..1000).map(|id| json!({"name": "x", "id": id})).collect();
+///
+/// assert_eq!(par_to_vec(&records).unwrap(), to_vec(&records).unwrap());
+/// ```
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys. Of several failing elements the error of
+/// the first one is returned.
+pub fn par_to_vec<T: Serialize + Sync>(values: &[T]) -> Result<Vec<u8>, Error> {
+    let chunks = rayon::current_num_threads() * CHUNKS_PER_THREAD;
+    let chunk_len = values.len().div_ceil(chunks).max(1);
+    let chunks: Vec<Result<Vec<u8>, Error>> = values
+        .par_chunks(chunk_len)
+        .enumerate()
+        .map_init(Canonicalizer::new, |canonicalizer, (chunk, values)| {
+            let mut output = Vec::new();
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    output.push(b',');
+                }
+                canonicalizer
+                    .canonicalize_into(value, &mut output)
+                    .map_err(|error| error.in_element(chunk * chunk_len + index))?;
+            }
+            Ok(output)
+        })
+        .collect();
+
+    let len = chunks.iter().flatten().map(Vec::len).sum::<usize>() + chunks.len() + 1;
+    let mut output = Vec::with_capacity(len);
+    output.push(b'[');
+    for (index, chunk) in chunks.into_iter().enumerate() {
+        if index > 0 {
+            output.push(b',');
+        }
+        output.extend_from_slice(&chunk?);
+    }
+    output.push(b']');
+    Ok(output)
+}
+
+/// Serialize every value of a batch as JCS on its own, in parallel on the rayon thread pool.
+///
+/// Returns the canonical form or the error of every value in the order of the batch, each the same
+/// as the one of [to_vec](crate::to_vec) for the value.
+///
+/// # Usage
+/// ```
+/// use serde_json::json;
+/// use serde_json_canonicalizer::canonicalize_batch;
+///
+/// let batch = [json!({"b": 1, "a": 2}), json!([1.0])];
+/// let canonical: Vec<_> = canonicalize_batch(&batch)
+///     .into_iter()
+///     .collect::<Result<_, _>>()
+///     .unwrap();
+///
+/// assert_eq!(canonical, [&br#"{"a":2,"b":1}"#[..], b"[1]"]);
+/// ```
+pub fn canonicalize_batch<T: Serialize + Sync>(values: &[T]) -> Vec<Result<Vec<u8>, Error>> {
+    values
+        .par_iter()
+        .map_init(Canonicalizer::new, |canonicalizer, value| {
+            let mut output = Vec::new();
+            canonicalizer.canonicalize_into(value, &mut output)?;
+            Ok(output)
+        })
+        .collect()
+}
@@ -0,0 +1,376 @@
+//! Canonicalizing JSON text from a reader without parsing it into a [serde_json::Value] first.
+//!
+//! The tokens read by the [serde_json] deserializer are written to the [JcsSerializer] as they
+//! arrive. Arrays and scalars pass straight through to the writer, only objects are buffered by
+//! the formatter until they end, so the memory used is proportional to the largest object.
+
+use std::{fmt, io};
+
+use crate::{
+    error::Error,
+    jcs::{format_escaped_str, IntegerPolicy, JcsFormatter, JcsSerializer},
+};
+use serde::{
+    de::{self, DeserializeSeed, MapAccess, SeqAccess, Visitor},
+    ser::Serializer as _,
+};
+
+/// Pipe JSON text from a reader into JCS written to a writer, without holding the whole document
+/// in memory.
+///
+/// Arrays are written out element by element as they are read, objects are written out when they
+/// end, so memory usage is proportional to the largest object in the input instead of the size of
+/// the input. The output is the same as the one of [pipe](crate::pipe): of duplicate keys the last
+/// one is kept.
+///
+/// Neither the reader nor the writer are buffered, wrap them into an [io::BufReader] and an
+/// [io::BufWriter] when small reads or writes are expensive, such as with files or sockets.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::pipe_reader;
+///
+/// let input = r#"[{"b": false, "c": 12e1, "a": "Hello!"}, 1.0]"#;
+/// let mut output = Vec::new();
+/// pipe_reader(input.as_bytes(), &mut output).unwrap();
+///
+/// assert_eq!(output, br#"[{"a":"Hello!","b":false,"c":120},1]"#);
+/// ```
+///
+/// # Errors
+///
+/// Fails on malformed JSON, unicode lone surrogates and numbers out of range the same way as
+/// [serde_json::from_reader], or if reading or writing fails. Whatever was written before the error
+/// stays in the writer.
+pub fn pipe_reader<R, W>(reader: R, writer: W) -> Result<(), Error>
+where
+    R: io::Read,
+    W: io::Write,
+{
+    let mut transcoder = Transcoder {
+        serializer: JcsSerializer::from_parts(
+            writer,
+            JcsFormatter::keeping_last_duplicate(),
+            IntegerPolicy::default(),
+        ),
+        error: None,
+    };
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let result = Transcode(&mut transcoder).deserialize(&mut deserializer);
+    // errors of the serializer are passed through the deserializer as a custom error
+    if let Some(error) = transcoder.error {
+        return Err(error);
+    }
+    result?;
+    Ok(deserializer.end()?)
+}
+
+/// The serializer the tokens are written to.
+struct Transcoder<W> {
+    serializer: JcsSerializer<W>,
+    /// The error of the serializer, if writing a token failed.
+    error: Option<Error>,
+}
+
+impl<W: io::Write> Transcoder<W> {
+    /// Keeps the error of the serializer and fails the deserialization.
+    fn written<E: de::Error>(&mut self, result: Result<(), Error>) -> Result<(), E> {
+        result.map_err(|error| {
+            let custom = E::custom(&error);
+            self.error = Some(error);
+            custom
+        })
+    }
+
+    /// Writes a token with the formatter.
+    fn write<E, F>(&mut self, write: F) -> Result<(), E>
+    where
+        E: de::Error,
+        F: FnOnce(&mut W, &mut JcsFormatter) -> io::Result<()>,
+    {
+        let (writer, formatter) = self.serializer.parts_mut();
+        let result = write(writer, formatter).map_err(Error::Io);
+        self.written(result)
+    }
+}
+
+/// Transcodes a single value.
+struct Transcode<'a, W>(&'a mut Transcoder<W>);
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for Transcode<'_, W> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, W: io::Write> Visitor<'de> for Transcode<'_, W> {
+    type Value = ();
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<(), E> {
+        let result = self.0.serializer.serialize_bool(value);
+        self.0.written(result)
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<(), E> {
+        let result = self.0.serializer.serialize_i64(value);
+        self.0.written(result)
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<(), E> {
+        let result = self.0.serializer.serialize_u64(value);
+        self.0.written(result)
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<(), E> {
+        let result = self.0.serializer.serialize_f64(value);
+        self.0.written(result)
+    }
+
+    fn visit_str<E: de::Error>(self, value: &str) -> Result<(), E> {
+        self.0
+            .write(|writer, formatter| format_escaped_str(writer, formatter, value))
+    }
+
+    fn visit_unit<E: de::Error>(self) -> Result<(), E> {
+        self.0
+            .write(|writer, formatter| formatter.write_null(writer))
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<(), A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        self.0
+            .write(|writer, formatter| formatter.begin_array(writer))?;
+        let mut first = true;
+        while let Some(()) = seq.next_element_seed(ArrayValue {
+            transcoder: &mut *self.0,
+            first,
+        })? {
+            first = false;
+        }
+        self.0
+            .write(|writer, formatter| formatter.end_array(writer))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<(), A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        self.0
+            .write(|writer, formatter| formatter.begin_object(writer))?;
+        let mut first = true;
+        while let Some(()) = map.next_key_seed(ObjectKey {
+            transcoder: &mut *self.0,
+            first,
+        })? {
+            first = false;
+            map.next_value_seed(ObjectValue(&mut *self.0))?;
+        }
+        self.0
+            .write(|writer, formatter| formatter.end_object(writer))
+    }
+}
+
+/// Transcodes an element of an array, only called when there is one.
+struct ArrayValue<'a, W> {
+    transcoder: &'a mut Transcoder<W>,
+    first: bool,
+}
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for ArrayValue<'_, W> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let first = self.first;
+        self.transcoder
+            .write(|writer, formatter| formatter.begin_array_value(writer, first))?;
+        deserializer.deserialize_any(Transcode(&mut *self.transcoder))?;
+        self.transcoder
+            .write(|writer, formatter| formatter.end_array_value(writer))
+    }
+}
+
+/// Transcodes the key of an object property, only called when there is one.
+struct ObjectKey<'a, W> {
+    transcoder: &'a mut Transcoder<W>,
+    first: bool,
+}
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for ObjectKey<'_, W> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        let first = self.first;
+        self.transcoder
+            .write(|writer, formatter| formatter.begin_object_key(writer, first))?;
+        // JSON keys are always strings
+        deserializer.deserialize_str(Transcode(&mut *self.transcoder))?;
+        self.transcoder
+            .write(|writer, formatter| formatter.end_object_key(writer))
+    }
+}
+
+/// Transcodes the value of an object property.
+struct ObjectValue<'a, W>(&'a mut Transcoder<W>);
+
+impl<'de, W: io::Write> DeserializeSeed<'de> for ObjectValue<'_, W> {
+    type Value = ();
+
+    fn deserialize<D>(self, deserializer: D) -> Result<(), D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        self.0
+            .write(|writer, formatter| formatter.begin_object_value(writer))?;
+        deserializer.deserialize_any(Transcode(&mut *self.0))?;
+        self.0
+            .write(|writer, formatter| formatter.end_object_value(writer))
+    }
+}
+
+/// Canonicalizes JSON text pushed in chunks, for readers that cannot be read from synchronously.
+///
+/// The elements of a top-level array are canonicalized with [pipe_reader] as soon as the next
+/// comma or the closing bracket arrives, so only a single element is held in memory at a time.
+/// Any other value is collected until the input ends.
+#[cfg(feature = "async")]
+#[derive(Default)]
+pub(crate) struct PushPipe {
+    state: PushState,
+    /// Input of the value or array element being read.
+    pending: Vec<u8>,
+    /// Canonical output that has not been taken yet.
+    output: Vec<u8>,
+    /// Nesting of the array element being read.
+    depth: usize,
+    in_string: bool,
+    escaped: bool,
+    elements: usize,
+}
+
+#[cfg(feature = "async")]
+#[derive(Default, PartialEq)]
+enum PushState {
+    #[default]
+    Start,
+    Value,
+    Elements,
+    Done,
+}
+
+#[cfg(feature = "async")]
+impl PushPipe {
+    /// Reads the next chunk of the input.
+    pub(crate) fn push(&mut self, input: &[u8]) -> Result<(), Error> {
+        let mut start = 0;
+        for (index, &byte) in input.iter().enumerate() {
+            match self.state {
+                PushState::Start => match byte {
+                    b' ' | b'\t' | b'\n' | b'\r' => start = index + 1,
+                    b'[' => {
+                        self.output.push(b'[');
+                        self.state = PushState::Elements;
+                        start = index + 1;
+                    }
+                    _ => {
+                        self.state = PushState::Value;
+                        break;
+                    }
+                },
+                PushState::Value => break,
+                PushState::Elements if self.in_string => match byte {
+                    _ if self.escaped => self.escaped = false,
+                    b'\\' => self.escaped = true,
+                    b'"' => self.in_string = false,
+                    _ => {}
+                },
+                PushState::Elements => match byte {
+                    b'"' => self.in_string = true,
+                    b'[' | b'{' => self.depth += 1,
+                    b']' | b'}' if self.depth > 0 => self.depth -= 1,
+                    b',' | b']' if self.depth == 0 => {
+                        self.pending.extend_from_slice(&input[start..index]);
+                        start = index + 1;
+                        self.end_element(byte == b']')?;
+                    }
+                    _ => {}
+                },
+                PushState::Done => match byte {
+                    b' ' | b'\t' | b'\n' | b'\r' => start = index + 1,
+                    _ => {
+                        return Err(Error::Json(de::Error::custom(
+                            "trailing characters after the array",
+                        )))
+                    }
+                },
+            }
+        }
+        if self.state != PushState::Done {
+            self.pending.extend_from_slice(&input[start..]);
+        }
+        Ok(())
+    }
+
+    /// Canonicalizes the array element that was read.
+    fn end_element(&mut self, last: bool) -> Result<(), Error> {
+        if self.pending.iter().all(u8::is_ascii_whitespace) {
+            if !last || self.elements > 0 {
+                return Err(Error::Json(de::Error::custom("expected an array element")));
+            }
+        } else {
+            if self.elements > 0 {
+                self.output.push(b',');
+            }
+            pipe_reader(self.pending.as_slice(), &mut self.output)?;
+            self.elements += 1;
+        }
+        self.pending.clear();
+        if last {
+            self.output.push(b']');
+            self.state = PushState::Done;
+        }
+        Ok(())
+    }
+
+    /// Canonicalizes what is left after the input ended.
+    pub(crate) fn finish(&mut self) -> Result<(), Error> {
+        match self.state {
+            PushState::Start | PushState::Value => {
+                pipe_reader(self.pending.as_slice(), &mut self.output)?;
+                self.pending.clear();
+                self.state = PushState::Done;
+                Ok(())
+            }
+            PushState::Elements => Err(Error::Json(de::Error::custom(
+                "EOF while parsing the array",
+            ))),
+            PushState::Done => Ok(()),
+        }
+    }
+
+    /// The canonical output that has not been taken yet.
+    pub(crate) fn output(&self) -> &[u8] {
+        &self.output
+    }
+
+    /// Takes the canonical output, keeping the buffer.
+    pub(crate) fn clear_output(&mut self) {
+        self.output.clear();
+    }
+}
@@ -0,0 +1,214 @@
+//! Strict I-JSON (RFC 7493) parsing for the input of canonicalization.
+//!
+//! RFC 8785 requires the input to be I-JSON. [serde_json] already rejects lone surrogates and
+//! numbers out of the double range, but silently keeps the last of duplicate object keys.
+
+use alloc::{
+    borrow::ToOwned,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::fmt;
+
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Error as DeError, MapAccess, SeqAccess, Visitor,
+};
+use serde_json::{Map, Value};
+
+use crate::Error;
+
+enum PathSegment {
+    Key(String),
+    Index(usize),
+}
+
+/// Formats the path as a JSON Pointer (RFC 6901).
+fn json_pointer(path: &[PathSegment]) -> String {
+    let mut pointer = String::new();
+    for segment in path {
+        pointer.push('/');
+        match segment {
+            PathSegment::Key(key) => pointer.push_str(&key.replace('~', "~0").replace('/', "~1")),
+            PathSegment::Index(index) => pointer.push_str(&index.to_string()),
+        }
+    }
+    pointer
+}
+
+/// Where the deserialization is, and why it failed.
+#[derive(Default)]
+struct State {
+    /// Path of the value being deserialized. On failure it is left pointing to the offending
+    /// element.
+    path: Vec<PathSegment>,
+    duplicate_key: bool,
+}
+
+/// Deserializes a [Value] rejecting duplicate keys, and keeps track of the path of the value
+/// being deserialized.
+struct StrictValue<'a> {
+    state: &'a mut State,
+}
+
+impl<'de> DeserializeSeed<'de> for StrictValue<'_> {
+    type Value = Value;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de> Visitor<'de> for StrictValue<'_> {
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid I-JSON value")
+    }
+
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::from(value))
+    }
+
+    fn visit_f64<E: DeError>(self, value: f64) -> Result<Value, E> {
+        serde_json::Number::from_f64(value)
+            .map(Value::Number)
+            .ok_or_else(|| E::custom("number out of range"))
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<Value, E> {
+        Ok(Value::String(value.to_owned()))
+    }
+
+    fn visit_string<E>(self, value: String) -> Result<Value, E> {
+        Ok(Value::String(value))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A>(self, mut seq: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        loop {
+            self.state.path.push(PathSegment::Index(values.len()));
+            match seq.next_element_seed(StrictValue { state: self.state })? {
+                Some(value) => values.push(value),
+                None => break,
+            }
+            self.state.path.pop();
+        }
+        self.state.path.pop();
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut values = Map::new();
+        while let Some(key) = map.next_key::<String>()? {
+            let duplicate = values.contains_key(&key);
+            self.state.path.push(PathSegment::Key(key));
+            if duplicate {
+                self.state.duplicate_key = true;
+                let pointer = json_pointer(&self.state.path);
+                return Err(A::Error::custom(format!(
+                    "duplicate object key at `{pointer}`"
+                )));
+            }
+            let value = map.next_value_seed(StrictValue { state: self.state })?;
+            if let Some(PathSegment::Key(key)) = self.state.path.pop() {
+                values.insert(key, value);
+            }
+        }
+        Ok(Value::Object(values))
+    }
+}
+
+/// Deserialize a [Value] from any deserializer, failing on duplicate object keys and on numbers
+/// that are not finite with the JSON Pointer of the offending element in the message.
+pub(crate) fn deserialize_strict<'de, D>(deserializer: D) -> Result<Value, D::Error>
+where
+    D: de::Deserializer<'de>,
+{
+    StrictValue {
+        state: &mut State::default(),
+    }
+    .deserialize(deserializer)
+}
+
+/// Parse an I-JSON value strictly, rejecting anything that cannot be canonicalized unambiguously.
+///
+/// Duplicate keys fail as [Error::DuplicateKey], and other errors inside of the document as
+/// [Error::Strict] with the JSON Pointer of the offending element.
+pub(crate) fn strict_value(json: &[u8]) -> Result<Value, Error> {
+    let mut state = State::default();
+    let mut deserializer = serde_json::Deserializer::from_slice(json);
+    StrictValue { state: &mut state }
+        .deserialize(&mut deserializer)
+        .and_then(|value| deserializer.end().map(|_| value))
+        .map_err(|source| {
+            if state.duplicate_key {
+                Error::DuplicateKey {
+                    path: json_pointer(&state.path),
+                }
+            } else if state.path.is_empty() {
+                Error::Json(source)
+            } else {
+                Error::Strict {
+                    path: json_pointer(&state.path),
+                    source,
+                }
+            }
+        })
+}
+
+/// Deserialize an instance of type `T` from I-JSON bytes.
+///
+/// Strict replacement for [serde_json::from_slice], useful to parse input that is going to be
+/// canonicalized or that has been signed in canonical form.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::from_slice_strict;
+///
+/// let error = from_slice_strict::<serde_json::Value>(br#"{"a":{"b":1,"b":2}}"#).unwrap_err();
+/// assert_eq!(error.path(), Some("/a/b"));
+/// ```
+///
+/// # Errors
+///
+/// Deserialization fails on malformed JSON, duplicate object keys, lone surrogate escapes, numbers
+/// out of the IEEE-754 double range, or if `T` cannot be deserialized from the parsed value.
+/// Duplicate keys fail as [Error::DuplicateKey], and other errors inside of the document as
+/// [Error::Strict], both with the JSON Pointer of the offending element.
+pub fn from_slice_strict<T: DeserializeOwned>(json: &[u8]) -> Result<T, Error> {
+    Ok(serde_json::from_value(strict_value(json)?)?)
+}
+
+/// Deserialize an instance of type `T` from an I-JSON string.
+///
+/// Strict replacement for [serde_json::from_str], see [from_slice_strict].
+///
+/// # Errors
+///
+/// Deserialization fails on malformed JSON, duplicate object keys, lone surrogate escapes, numbers
+/// out of the IEEE-754 double range, or if `T` cannot be deserialized from the parsed value.
+pub fn from_str_strict<T: DeserializeOwned>(json: &str) -> Result<T, Error> {
+    from_slice_strict(json.as_bytes())
+}
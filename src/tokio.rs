@@ -0,0 +1,3 @@
+//! Canonicalization into a tokio [AsyncWrite].
+
+crate::async_io::async_io!("tokio", ::tokio::io);
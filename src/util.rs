@@ -1,35 +1,87 @@
 //! Utility functions for JCS serialization and deserialization.
 //!
-//! Intended to be drop-in replacements for the [serde_json] equivalents.
+//! Mirror the [serde_json] equivalents, but fail with the [Error] of this crate, which carries the
+//! JSON Pointer of the offending value, instead of [serde_json::Error].
 
-use crate::jcs::JcsSerializer;
+use crate::{
+    error::Error,
+    io,
+    jcs::{IntegerPolicy, JcsSerializer},
+    strict::strict_value,
+    value::value_to_vec_with,
+};
+use alloc::{string::String, vec::Vec};
 use serde::Serialize;
-use std::io;
 
 /// Serialize the given data structure as a JCS byte vector.
 ///
-/// Drop in replacement for [serde_json::to_vec].
+/// Counterpart of [serde_json::to_vec], failing with [Error] instead of [serde_json::Error].
 ///
 /// # Errors
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to
 /// fail, or if `T` contains a map with non-string keys.
-pub fn to_vec<S: Serialize>(value: &S) -> serde_json::Result<Vec<u8>> {
+pub fn to_vec<S: Serialize>(value: &S) -> Result<Vec<u8>, Error> {
+    to_vec_with(value, IntegerPolicy::default())
+}
+
+/// Serialize the given data structure as a JCS byte vector, with the given [IntegerPolicy].
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::{to_vec_with, Error, IntegerPolicy};
+///
+/// let error = to_vec_with(&[9007199254740993u64], IntegerPolicy::Error).unwrap_err();
+/// assert!(matches!(error, Error::LossyInteger { .. }));
+/// ```
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, if `T` contains a map with non-string keys, or for integers that are not exact doubles
+/// with [IntegerPolicy::Error].
+pub fn to_vec_with<S: Serialize>(
+    value: &S,
+    integer_policy: IntegerPolicy,
+) -> Result<Vec<u8>, Error> {
     // copying the serde_json::to_vec buffer size
     let mut buffer = Vec::with_capacity(128);
-    to_writer(value, &mut buffer).map(|_| buffer)
+    to_writer_with(value, &mut buffer, integer_policy).map(|_| buffer)
 }
 
 /// Serialize the given data structure as a JCS UTF-8 string.
 ///
-/// Drop in replacement for [serde_json::to_string].
+/// Counterpart of [serde_json::to_string], failing with [Error] instead of [serde_json::Error].
 ///
 /// # Errors
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to
 /// fail, or if `T` contains a map with non-string keys.
-pub fn to_string<S: Serialize>(value: &S) -> serde_json::Result<String> {
-    let vec = to_vec(value)?;
+pub fn to_string<S: Serialize>(value: &S) -> Result<String, Error> {
+    to_string_with(value, IntegerPolicy::default())
+}
+
+/// Serialize the given data structure as a JCS UTF-8 string, with the given [IntegerPolicy].
+///
+/// # Usage
+/// ```
+/// use serde_json::json;
+/// use serde_json_canonicalizer::{to_string_with, IntegerPolicy};
+///
+/// let jcs = to_string_with(&json!({"id": 9007199254740993u64}), IntegerPolicy::String).unwrap();
+/// assert_eq!(jcs, r#"{"id":"9007199254740993"}"#);
+/// ```
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, if `T` contains a map with non-string keys, or for integers that are not exact doubles
+/// with [IntegerPolicy::Error].
+pub fn to_string_with<S: Serialize>(
+    value: &S,
+    integer_policy: IntegerPolicy,
+) -> Result<String, Error> {
+    let vec = to_vec_with(value, integer_policy)?;
     let string = unsafe {
         // We do not emit invalid UTF-8.
         String::from_utf8_unchecked(vec)
@@ -40,14 +92,29 @@ pub fn to_string<S: Serialize>(value: &S) -> serde_json::Result<String> {
 /// Serialize the given data structure as JCS into the I/O stream.
 /// Serialization guarantees it only feeds valid UTF-8 sequences to the writer.
 ///
-/// Drop in replacement for [serde_json::to_writer].
+/// Counterpart of [serde_json::to_writer], failing with [Error] instead of [serde_json::Error].
 ///
 /// # Errors
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to
 /// fail, or if `T` contains a map with non-string keys.
-pub fn to_writer<S: Serialize, W: io::Write>(value: &S, writer: &mut W) -> serde_json::Result<()> {
-    value.serialize(&mut JcsSerializer::new(writer))
+pub fn to_writer<S: Serialize, W: io::Write>(value: &S, writer: &mut W) -> Result<(), Error> {
+    to_writer_with(value, writer, IntegerPolicy::default())
+}
+
+/// Serialize the given data structure as JCS into the I/O stream, with the given [IntegerPolicy].
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, if `T` contains a map with non-string keys, for integers that are not exact doubles with
+/// [IntegerPolicy::Error], or if writing fails.
+pub fn to_writer_with<S: Serialize, W: io::Write>(
+    value: &S,
+    writer: &mut W,
+    integer_policy: IntegerPolicy,
+) -> Result<(), Error> {
+    value.serialize(&mut JcsSerializer::new(writer).with_integer_policy(integer_policy))
 }
 
 /// Pipe a JSON formatted string into a JCS formatted string.
@@ -70,8 +137,74 @@ pub fn to_writer<S: Serialize, W: io::Write>(value: &S, writer: &mut W) -> serde
 ///
 /// Serialization can fail if `T`'s implementation of `Serialize` decides to
 /// fail, or if `T` contains a map with non-string keys.
-pub fn pipe(json: &str) -> serde_json::Result<String> {
+pub fn pipe(json: &str) -> Result<String, Error> {
+    pipe_with(json, IntegerPolicy::default())
+}
+
+/// Pipe a JSON formatted string into a JCS formatted string, with the given [IntegerPolicy].
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::{pipe_with, IntegerPolicy};
+///
+/// let input = r#"{"account": 9007199254740993, "amount": 1.5}"#;
+///
+/// assert!(pipe_with(input, IntegerPolicy::Error).is_err());
+/// assert_eq!(
+///     pipe_with(input, IntegerPolicy::String).unwrap(),
+///     r#"{"account":"9007199254740993","amount":1.5}"#
+/// );
+/// ```
+///
+/// # Errors
+///
+/// Fails like [pipe], and for integers that are not exact doubles with [IntegerPolicy::Error].
+pub fn pipe_with(json: &str, integer_policy: IntegerPolicy) -> Result<String, Error> {
     let value: serde_json::Value = serde_json::from_str(json)?;
-    let jcs = to_string(&value)?;
-    Ok(jcs)
+    value_to_string(&value, integer_policy)
+}
+
+/// Pipe a JSON formatted string into a JCS formatted string, strictly requiring I-JSON input.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::pipe_strict;
+///
+/// assert_eq!(pipe_strict(r#"{"b": 1, "a": 2}"#).unwrap(), r#"{"a":2,"b":1}"#);
+/// assert!(pipe_strict(r#"{"a": 1, "a": 2}"#).is_err());
+/// ```
+///
+/// # Errors
+///
+/// Deserialization uses [from_str_strict](crate::from_str_strict), which fails on malformed JSON,
+/// duplicate object keys, unicode lone surrogates and numbers out of range, reporting the JSON
+/// Pointer of the offending element. Duplicate keys are reported as
+/// [Error::DuplicateKey](crate::Error::DuplicateKey), and other errors inside of the document as
+/// [Error::Strict](crate::Error::Strict).
+pub fn pipe_strict(json: &str) -> Result<String, Error> {
+    pipe_strict_with(json, IntegerPolicy::default())
+}
+
+/// Pipe a JSON formatted string into a JCS formatted string, strictly requiring I-JSON input, with
+/// the given [IntegerPolicy].
+///
+/// # Errors
+///
+/// Fails like [pipe_strict], and for integers that are not exact doubles with
+/// [IntegerPolicy::Error].
+pub fn pipe_strict_with(json: &str, integer_policy: IntegerPolicy) -> Result<String, Error> {
+    let value = strict_value(json.as_bytes())?;
+    value_to_string(&value, integer_policy)
+}
+
+fn value_to_string(
+    value: &serde_json::Value,
+    integer_policy: IntegerPolicy,
+) -> Result<String, Error> {
+    let vec = value_to_vec_with(value, integer_policy)?;
+    let string = unsafe {
+        // We do not emit invalid UTF-8.
+        String::from_utf8_unchecked(vec)
+    };
+    Ok(string)
 }
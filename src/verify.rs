@@ -0,0 +1,388 @@
+//! Verification that a JSON text is already in JCS canonical form.
+//!
+//! Useful when receiving signed payloads that must be processed byte-exact, the input is checked
+//! in place instead of being re-serialized and compared.
+
+use alloc::{string::String, vec::Vec};
+use core::{cmp::Ordering, fmt::Display};
+
+use crate::jcs::{compare_keys, format_escaped_str, JcsFormatter};
+
+/// Mirrors the recursion limit of [serde_json], anything nested deeper is not accepted by it.
+const RECURSION_LIMIT: usize = 128;
+
+/// The kind of deviation from the canonical form found by [verify_canonical].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Deviation {
+    /// Whitespace between tokens.
+    Whitespace,
+    /// An object property that is not sorted after the previous one.
+    UnsortedKey,
+    /// An object property with the same name as the previous one.
+    DuplicateKey,
+    /// A number that is not in the shortest round-trip form, or that is out of the double range.
+    NonCanonicalNumber,
+    /// A string escape sequence that is not needed, or not in its canonical form.
+    NonCanonicalEscape,
+    /// Input that is not valid I-JSON in the first place.
+    InvalidJson,
+}
+
+impl Display for Deviation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let description = match self {
+            Deviation::Whitespace => "whitespace",
+            Deviation::UnsortedKey => "unsorted object key",
+            Deviation::DuplicateKey => "duplicate object key",
+            Deviation::NonCanonicalNumber => "non-canonical number",
+            Deviation::NonCanonicalEscape => "non-canonical string escape",
+            Deviation::InvalidJson => "invalid JSON",
+        };
+        f.write_str(description)
+    }
+}
+
+/// The first deviation from the canonical form found by [verify_canonical].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NotCanonical {
+    offset: usize,
+    deviation: Deviation,
+}
+
+impl NotCanonical {
+    /// Byte offset of the first deviation in the input.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// The kind of the first deviation.
+    pub fn deviation(&self) -> Deviation {
+        self.deviation
+    }
+}
+
+impl Display for NotCanonical {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{} at byte offset {}", self.deviation, self.offset)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NotCanonical {}
+
+/// Check that the input is byte-exact JCS canonical JSON.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::is_canonical;
+///
+/// assert!(is_canonical(br#"{"a":"Hello!","b":false,"c":120}"#));
+/// assert!(!is_canonical(br#"{"b":false,"a":"Hello!"}"#));
+/// ```
+pub fn is_canonical(json: &[u8]) -> bool {
+    verify_canonical(json).is_ok()
+}
+
+/// Verify that the input is byte-exact JCS canonical JSON.
+///
+/// # Usage
+/// ```
+/// use serde_json_canonicalizer::{verify_canonical, Deviation};
+///
+/// let error = verify_canonical(br#"{"a":1.0}"#).unwrap_err();
+/// assert_eq!(error.offset(), 5);
+/// assert_eq!(error.deviation(), Deviation::NonCanonicalNumber);
+/// ```
+///
+/// # Errors
+///
+/// Returns the byte offset and kind of the first deviation from the canonical form.
+pub fn verify_canonical(json: &[u8]) -> Result<(), NotCanonical> {
+    let text = core::str::from_utf8(json).map_err(|error| NotCanonical {
+        offset: error.valid_up_to(),
+        deviation: Deviation::InvalidJson,
+    })?;
+    let mut verifier = Verifier {
+        text,
+        position: 0,
+        depth: 0,
+    };
+    verifier.value()?;
+    match verifier.peek() {
+        None => Ok(()),
+        Some(_) => Err(verifier.unexpected()),
+    }
+}
+
+struct Verifier<'a> {
+    text: &'a str,
+    position: usize,
+    depth: usize,
+}
+
+impl Verifier<'_> {
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.position).copied()
+    }
+
+    fn error_at(&self, offset: usize, deviation: Deviation) -> NotCanonical {
+        NotCanonical { offset, deviation }
+    }
+
+    /// Error for the byte at the current position, which is not the expected one.
+    fn unexpected(&self) -> NotCanonical {
+        let deviation = match self.peek() {
+            Some(b' ' | b'\t' | b'\n' | b'\r') => Deviation::Whitespace,
+            _ => Deviation::InvalidJson,
+        };
+        self.error_at(self.position, deviation)
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), NotCanonical> {
+        if self.peek() == Some(byte) {
+            self.position += 1;
+            Ok(())
+        } else {
+            Err(self.unexpected())
+        }
+    }
+
+    fn value(&mut self) -> Result<(), NotCanonical> {
+        match self.peek() {
+            Some(b'{') => self.nested(Self::object),
+            Some(b'[') => self.nested(Self::array),
+            Some(b'"') => self.string().map(|_| ()),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b't') => self.literal("true"),
+            Some(b'f') => self.literal("false"),
+            Some(b'n') => self.literal("null"),
+            _ => Err(self.unexpected()),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Self) -> Result<(), NotCanonical>,
+    ) -> Result<(), NotCanonical> {
+        if self.depth == RECURSION_LIMIT {
+            return Err(self.error_at(self.position, Deviation::InvalidJson));
+        }
+        self.depth += 1;
+        parse(self)?;
+        self.depth -= 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, literal: &str) -> Result<(), NotCanonical> {
+        if self.text[self.position..].starts_with(literal) {
+            self.position += literal.len();
+            Ok(())
+        } else {
+            Err(self.error_at(self.position, Deviation::InvalidJson))
+        }
+    }
+
+    fn array(&mut self) -> Result<(), NotCanonical> {
+        self.expect(b'[')?;
+        if self.peek() == Some(b']') {
+            self.position += 1;
+            return Ok(());
+        }
+        loop {
+            self.value()?;
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b']') => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    /// Properties have to be sorted by their UTF-16 code units, the same order the
+    /// [JcsFormatter] writes them in.
+    fn object(&mut self) -> Result<(), NotCanonical> {
+        self.expect(b'{')?;
+        if self.peek() == Some(b'}') {
+            self.position += 1;
+            return Ok(());
+        }
+        let mut previous_key: Option<String> = None;
+        loop {
+            let key_offset = self.position;
+            if self.peek() != Some(b'"') {
+                return Err(self.unexpected());
+            }
+            let key = self.string()?;
+            if let Some(previous_key) = &previous_key {
+                match compare_keys(previous_key, &key) {
+                    Ordering::Less => {}
+                    Ordering::Equal => {
+                        return Err(self.error_at(key_offset, Deviation::DuplicateKey))
+                    }
+                    Ordering::Greater => {
+                        return Err(self.error_at(key_offset, Deviation::UnsortedKey))
+                    }
+                }
+            }
+            previous_key = Some(key);
+            self.expect(b':')?;
+            self.value()?;
+            match self.peek() {
+                Some(b',') => self.position += 1,
+                Some(b'}') => {
+                    self.position += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.unexpected()),
+            }
+        }
+    }
+
+    /// Numbers have to be in the form the [JcsFormatter] writes them.
+    fn number(&mut self) -> Result<(), NotCanonical> {
+        let start = self.position;
+        let digits = |verifier: &mut Self| {
+            let digits_start = verifier.position;
+            while matches!(verifier.peek(), Some(b'0'..=b'9')) {
+                verifier.position += 1;
+            }
+            if verifier.position == digits_start {
+                Err(verifier.error_at(verifier.position, Deviation::InvalidJson))
+            } else {
+                Ok(())
+            }
+        };
+
+        if self.peek() == Some(b'-') {
+            self.position += 1;
+        }
+        if self.peek() == Some(b'0') {
+            self.position += 1;
+        } else {
+            digits(self)?;
+        }
+        if self.peek() == Some(b'.') {
+            self.position += 1;
+            digits(self)?;
+        }
+        if matches!(self.peek(), Some(b'e' | b'E')) {
+            self.position += 1;
+            if matches!(self.peek(), Some(b'+' | b'-')) {
+                self.position += 1;
+            }
+            digits(self)?;
+        }
+
+        let token = &self.text[start..self.position];
+        let mut canonical = Vec::with_capacity(token.len());
+        let canonical_form = token.parse::<f64>().ok().and_then(|number| {
+            JcsFormatter::default()
+                .write_f64(&mut canonical, number)
+                .ok()
+        });
+        if canonical_form.is_some() && canonical == token.as_bytes() {
+            Ok(())
+        } else {
+            Err(self.error_at(start, Deviation::NonCanonicalNumber))
+        }
+    }
+
+    /// Strings have to be escaped the way the [JcsFormatter] escapes them.
+    /// Returns the unescaped string.
+    fn string(&mut self) -> Result<String, NotCanonical> {
+        self.expect(b'"')?;
+        let mut unescaped = String::new();
+        let mut run_start = self.position;
+        loop {
+            match self.peek() {
+                Some(b'"') => {
+                    unescaped.push_str(&self.text[run_start..self.position]);
+                    self.position += 1;
+                    break;
+                }
+                Some(b'\\') => {
+                    unescaped.push_str(&self.text[run_start..self.position]);
+                    unescaped.push(self.escape()?);
+                    run_start = self.position;
+                }
+                Some(0x00..=0x1f) | None => {
+                    return Err(self.error_at(self.position, Deviation::InvalidJson))
+                }
+                Some(_) => self.position += 1,
+            }
+        }
+
+        Ok(unescaped)
+    }
+
+    /// Decodes an escape sequence and checks that it is escaped the way the [JcsFormatter] would
+    /// escape the character. Characters that are not escaped are always in canonical form.
+    fn escape(&mut self) -> Result<char, NotCanonical> {
+        let start = self.position;
+        let escaped = self.decode_escape(start)?;
+        let mut canonical = Vec::with_capacity(8);
+        format_escaped_str(
+            &mut canonical,
+            &mut JcsFormatter::default(),
+            escaped.encode_utf8(&mut [0; 4]),
+        )
+        .map_err(|_| self.error_at(start, Deviation::InvalidJson))?;
+        // Strip the quotes
+        if canonical[1..canonical.len() - 1] == self.text.as_bytes()[start..self.position] {
+            Ok(escaped)
+        } else {
+            Err(self.error_at(start, Deviation::NonCanonicalEscape))
+        }
+    }
+
+    fn decode_escape(&mut self, start: usize) -> Result<char, NotCanonical> {
+        self.position += 1;
+        let escaped = match self.peek() {
+            Some(b'"') => '"',
+            Some(b'\\') => '\\',
+            Some(b'/') => '/',
+            Some(b'b') => '\x08',
+            Some(b'f') => '\x0c',
+            Some(b'n') => '\n',
+            Some(b'r') => '\r',
+            Some(b't') => '\t',
+            Some(b'u') => {
+                self.position += 1;
+                let high = self.hex_escape(start)?;
+                let code_point = if (0xd800..0xdc00).contains(&high) {
+                    if !self.text[self.position..].starts_with("\\u") {
+                        return Err(self.error_at(start, Deviation::InvalidJson));
+                    }
+                    self.position += 2;
+                    let low = self.hex_escape(start)?;
+                    if !(0xdc00..0xe000).contains(&low) {
+                        return Err(self.error_at(start, Deviation::InvalidJson));
+                    }
+                    0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                } else {
+                    high
+                };
+                // Lone low surrogates are rejected here
+                return char::from_u32(code_point)
+                    .ok_or_else(|| self.error_at(start, Deviation::InvalidJson));
+            }
+            _ => return Err(self.error_at(start, Deviation::InvalidJson)),
+        };
+        self.position += 1;
+        Ok(escaped)
+    }
+
+    fn hex_escape(&mut self, escape_start: usize) -> Result<u32, NotCanonical> {
+        let hex = self
+            .text
+            .get(self.position..self.position + 4)
+            .filter(|hex| hex.bytes().all(|byte| byte.is_ascii_hexdigit()))
+            .ok_or_else(|| self.error_at(escape_start, Deviation::InvalidJson))?;
+        self.position += 4;
+        Ok(u32::from_str_radix(hex, 16).expect("validated hex digits"))
+    }
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "base64")]
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use serde_json_canonicalizer::to_vec;
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Envelope {
+    #[serde(with = "serde_json_canonicalizer::as_bytes")]
+    payload: Value,
+    signature: String,
+}
+
+#[test]
+fn field_is_base64url_of_jcs_bytes() {
+    let payload = json!({"b": [1.5, "~~~???"], "a": "ü"});
+    let envelope = Envelope {
+        payload: payload.clone(),
+        signature: "sig".into(),
+    };
+    let json = serde_json::to_value(&envelope).unwrap();
+
+    let encoded = json["payload"].as_str().unwrap();
+    assert!(!encoded.contains(['+', '/', '=']));
+    assert_eq!(
+        URL_SAFE_NO_PAD.decode(encoded).unwrap(),
+        to_vec(&payload).unwrap()
+    );
+    assert_eq!(serde_json::from_value::<Envelope>(json).unwrap(), envelope);
+}
+
+#[test]
+fn errors() {
+    let padded = json!({"payload": "eyJhIjoxfQ==", "signature": ""});
+    assert!(serde_json::from_value::<Envelope>(padded).is_err());
+
+    let duplicate = URL_SAFE_NO_PAD.encode(br#"{"a":1,"a":2}"#);
+    let duplicate = json!({"payload": duplicate, "signature": ""});
+    assert!(serde_json::from_value::<Envelope>(duplicate).is_err());
+
+    let envelope = json!({"payload": URL_SAFE_NO_PAD.encode(b"{\"a\":1}"), "signature": ""});
+    assert_eq!(
+        serde_json::from_value::<Envelope>(envelope)
+            .unwrap()
+            .payload,
+        json!({"a": 1})
+    );
+}
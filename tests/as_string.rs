@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Claims {
+    sub: String,
+    scopes: Vec<String>,
+    exp: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Message {
+    id: u32,
+    #[serde(with = "serde_json_canonicalizer::as_string")]
+    claims: Claims,
+    #[serde(with = "serde_json_canonicalizer::as_string")]
+    extra: HashMap<String, f64>,
+}
+
+fn message() -> Message {
+    Message {
+        id: 7,
+        claims: Claims {
+            sub: "alice".into(),
+            scopes: vec!["read".into(), "write".into()],
+            exp: 1_700_000_000,
+        },
+        extra: HashMap::from([("z".into(), 1.5), ("a".into(), 100.0)]),
+    }
+}
+
+#[test]
+fn fields_are_jcs_strings() {
+    let json = serde_json::to_value(message()).unwrap();
+
+    assert_eq!(
+        json,
+        json!({
+            "id": 7,
+            "claims": r#"{"exp":1700000000,"scopes":["read","write"],"sub":"alice"}"#,
+            "extra": r#"{"a":100,"z":1.5}"#,
+        })
+    );
+}
+
+#[test]
+fn round_trip() {
+    let json = serde_json::to_string_pretty(&message()).unwrap();
+    assert_eq!(serde_json::from_str::<Message>(&json).unwrap(), message());
+}
+
+#[test]
+fn deserializes_non_canonical_strings() {
+    let json = json!({
+        "id": 7,
+        "claims": r#"{ "sub": "alice", "scopes": ["read", "write"], "exp": 1700000000 }"#,
+        "extra": r#"{"z": 1.5, "a": 1e2}"#,
+    });
+    assert_eq!(serde_json::from_value::<Message>(json).unwrap(), message());
+}
+
+#[test]
+fn errors() {
+    #[derive(Serialize, Deserialize, Debug)]
+    struct Field(#[serde(with = "serde_json_canonicalizer::as_string")] Value);
+
+    assert!(serde_json::to_string(&Field(json!({"a": 1}))).is_ok());
+    assert!(serde_json::from_value::<Field>(json!(r#"{"a":1,"a":2}"#)).is_err());
+    assert!(serde_json::from_value::<Field>(json!({"a": 1})).is_err());
+
+    #[derive(Serialize)]
+    struct Float(#[serde(with = "serde_json_canonicalizer::as_string")] f64);
+    let error = serde_json::to_string(&Float(f64::NAN)).unwrap_err();
+    assert!(error.to_string().contains("NaN"));
+}
@@ -0,0 +1,182 @@
+#![cfg(feature = "async")]
+
+use std::{
+    fs::read_dir,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use serde_json::json;
+use test_case::test_case;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+use serde_json_canonicalizer::{
+    futures, pipe, to_vec, tokio::pipe_async, tokio::to_async_writer, Error,
+};
+
+/// Hands out the input a few bytes at a time.
+struct Trickle<'a> {
+    input: &'a [u8],
+    chunk: usize,
+}
+
+impl AsyncRead for Trickle<'_> {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let count = self.chunk.min(self.input.len()).min(buf.remaining());
+        buf.put_slice(&self.input[..count]);
+        self.input = &self.input[count..];
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Records the size of every write, and whether the writer was flushed after the last one.
+#[derive(Default)]
+struct Recorder {
+    output: Vec<u8>,
+    writes: Vec<usize>,
+    flushed: bool,
+}
+
+impl AsyncWrite for Recorder {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        _cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        self.output.extend_from_slice(buf);
+        self.writes.push(buf.len());
+        self.flushed = false;
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        self.flushed = true;
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+fn large_input() -> String {
+    serde_json::to_string(
+        &(0..20_000)
+            .map(|index| json!({"index": index, "name": format!("\"{index}\""), "tags": [index, {}]}))
+            .collect::<Vec<_>>(),
+    )
+    .unwrap()
+}
+
+async fn pipe_trickle(input: &str, chunk: usize) -> Result<String, Error> {
+    let mut output = Vec::new();
+    let reader = Trickle {
+        input: input.as_bytes(),
+        chunk,
+    };
+    pipe_async(reader, &mut output).await?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+#[tokio::test]
+async fn to_async_writer_matches_to_vec() {
+    let value = json!({"b": [1e30, "\u{20ac}"], "a": {"d": null, "c": true}});
+    let mut tokio_output = Vec::new();
+    let mut futures_output = Vec::new();
+    to_async_writer(&value, &mut tokio_output).await.unwrap();
+    futures::to_async_writer(&value, &mut futures_output)
+        .await
+        .unwrap();
+
+    assert_eq!(tokio_output, to_vec(&value).unwrap());
+    assert_eq!(futures_output, to_vec(&value).unwrap());
+}
+
+#[tokio::test]
+async fn reference_testdata() {
+    for file in read_dir("tests/resources/testdata/input").unwrap() {
+        let path = file.unwrap().path();
+        let input = std::fs::read_to_string(&path).unwrap();
+
+        for chunk in [1, 7, 8192] {
+            assert_eq!(
+                pipe_trickle(&input, chunk).await.unwrap(),
+                pipe(&input).unwrap(),
+                "{path:?}"
+            );
+        }
+    }
+}
+
+#[test_case(" [ ] " ; "empty array")]
+#[test_case("[[],{}]" ; "empty elements")]
+#[test_case(r#"["]", "\"", "\\", "[{", {"a]": "}", "b": [[1, 2], {}]}, [{"c": ["d"]}]]"# ; "brackets in strings")]
+#[test_case(r#" {"b": [1, 2], "a": "x"} "# ; "object")]
+#[test_case(r#""a,]""# ; "string")]
+#[test_case("12e1" ; "number")]
+#[tokio::test]
+async fn same_output_as_pipe(input: &str) {
+    for chunk in [1, 2, 3, 1024] {
+        assert_eq!(
+            pipe_trickle(input, chunk).await.unwrap(),
+            pipe(input).unwrap()
+        );
+    }
+
+    let mut output = Vec::new();
+    futures::pipe_async(input.as_bytes(), &mut output)
+        .await
+        .unwrap();
+    assert_eq!(output, pipe(input).unwrap().as_bytes());
+}
+
+#[tokio::test]
+async fn writes_in_chunks_and_flushes() {
+    let input = large_input();
+    let value: serde_json::Value = serde_json::from_str(&input).unwrap();
+
+    let mut recorder = Recorder::default();
+    to_async_writer(&value, &mut recorder).await.unwrap();
+    assert_eq!(recorder.output, to_vec(&value).unwrap());
+    assert!(recorder.writes.len() > 1);
+    assert!(recorder.writes.iter().all(|&len| len <= 8 * 1024));
+    assert!(recorder.flushed);
+
+    let mut recorder = Recorder::default();
+    pipe_async(input.as_bytes(), &mut recorder).await.unwrap();
+    assert_eq!(recorder.output, pipe(&input).unwrap().as_bytes());
+    assert!(recorder.writes.len() > 1);
+    assert!(recorder.flushed);
+}
+
+#[tokio::test]
+async fn large_array() {
+    let input = large_input();
+
+    assert_eq!(
+        pipe_trickle(&input, 4096).await.unwrap(),
+        pipe(&input).unwrap()
+    );
+}
+
+#[test_case("" ; "empty input")]
+#[test_case("[1,]" ; "trailing comma")]
+#[test_case("[,1]" ; "leading comma")]
+#[test_case("[1,,2]" ; "double comma")]
+#[test_case("[1 2]" ; "missing comma")]
+#[test_case("[1] 2" ; "trailing value")]
+#[test_case("[1" ; "unterminated array")]
+#[test_case("[{]" ; "mismatched brackets")]
+#[test_case("{} {}" ; "two objects")]
+#[test_case(r#"["\ud800"]"# ; "lone surrogate")]
+#[tokio::test]
+async fn malformed_input(input: &str) {
+    assert!(pipe(input).is_err());
+    for chunk in [1, 1024] {
+        assert!(pipe_trickle(input, chunk).await.is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use test_case::test_case;
+
+use serde_json_canonicalizer::{to_string, Canonical};
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+struct Payload {
+    to: String,
+    amount: f64,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope {
+    version: u32,
+    payload: Canonical<Payload>,
+}
+
+#[test]
+fn serializes_as_a_string_with_any_serializer() {
+    let payload = Canonical::new(Payload {
+        to: "bob".into(),
+        amount: 1e21,
+    })
+    .unwrap();
+    let envelope = Envelope {
+        version: 1,
+        payload,
+    };
+
+    let pretty = serde_json::to_string_pretty(&envelope).unwrap();
+    let value: Value = serde_json::from_str(&pretty).unwrap();
+    assert_eq!(value["payload"], r#"{"amount":1e+21,"to":"bob"}"#);
+    assert_eq!(
+        to_string(&envelope).unwrap(),
+        r#"{"payload":"{\"amount\":1e+21,\"to\":\"bob\"}","version":1}"#
+    );
+}
+
+#[test]
+fn round_trip_keeps_value_and_bytes() {
+    let json = r#"{"version":1,"payload":"{\"amount\":12.5,\"to\":\"€\"}"}"#;
+    let envelope: Envelope = serde_json::from_str(json).unwrap();
+
+    assert_eq!(
+        envelope.payload.as_bytes(),
+        r#"{"amount":12.5,"to":"€"}"#.as_bytes()
+    );
+    assert_eq!(
+        envelope.payload.value(),
+        &Payload {
+            to: "€".into(),
+            amount: 12.5,
+        }
+    );
+    assert_eq!(envelope.payload.amount, 12.5);
+    let (payload, canonical) = envelope.payload.into_parts();
+    assert_eq!(Canonical::new(payload).unwrap().as_str(), canonical);
+}
+
+#[test_case(r#"{"to":"bob","amount":1}"# ; "unsorted")]
+#[test_case(r#"{"amount":1.0,"to":"bob"}"# ; "number")]
+#[test_case(r#"{"amount": 1,"to":"bob"}"# ; "whitespace")]
+#[test_case(r#"{"amount":1,"amount":2,"to":"bob"}"# ; "duplicate key")]
+#[test_case(r#"{"amount":1"# ; "malformed")]
+fn rejects_non_canonical(payload: &str) {
+    let error = serde_json::from_value::<Canonical<Payload>>(json!(payload)).unwrap_err();
+    assert!(
+        error.to_string().starts_with("JSON is not canonical"),
+        "{error}"
+    );
+}
+
+#[test]
+fn rejects_mismatched_types() {
+    assert!(serde_json::from_value::<Canonical<Payload>>(json!(r#"{"amount":"1"}"#)).is_err());
+    assert!(serde_json::from_value::<Canonical<Payload>>(json!({"amount": 1})).is_err());
+}
+
+#[test]
+fn serialization_errors() {
+    assert!(Canonical::new(f64::NAN).is_err());
+}
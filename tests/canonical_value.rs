@@ -0,0 +1,106 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use test_case::test_case;
+
+use serde_json_canonicalizer::{to_string, CanonicalValue, Error};
+
+fn canonical(value: Value) -> CanonicalValue {
+    CanonicalValue::new(&value).unwrap()
+}
+
+#[test_case(json!(1.0), json!(1) ; "integral float")]
+#[test_case(json!(1e21), json!(1000000000000000000000u128 as f64) ; "exponent")]
+#[test_case(json!({"b": 1, "a": [2.0]}), json!({"a": [2], "b": 1}) ; "property order")]
+#[test_case(json!(-0.0), json!(0) ; "negative zero")]
+fn equal_by_canonical_form(left: Value, right: Value) {
+    assert_eq!(canonical(left), canonical(right));
+}
+
+#[test_case(json!(1), json!("1") ; "number and string")]
+#[test_case(json!([1, 2]), json!([2, 1]) ; "element order")]
+#[test_case(json!({"a": null}), json!({}) ; "null property")]
+fn different_values_differ(left: Value, right: Value) {
+    assert_ne!(canonical(left), canonical(right));
+}
+
+#[test]
+fn deduplicates_in_hash_map() {
+    let documents = [
+        json!({"id": 1.0, "tags": ["x"]}),
+        json!({"tags": ["x"], "id": 1}),
+        json!({"id": 2, "tags": ["x"]}),
+    ];
+    let mut counts = HashMap::new();
+    for document in &documents {
+        *counts.entry(canonical(document.clone())).or_insert(0) += 1;
+    }
+    assert_eq!(counts.len(), 2);
+    assert_eq!(counts[&canonical(json!({"id": 1, "tags": ["x"]}))], 2);
+}
+
+#[test]
+fn orders_by_canonical_bytes() {
+    let values = [
+        json!(10),
+        json!(9),
+        json!("a"),
+        json!([]),
+        json!({"b": 1, "a": 2}),
+    ];
+    let set: BTreeSet<_> = values.into_iter().map(canonical).collect();
+    let ordered: Vec<_> = set.iter().map(CanonicalValue::as_str).collect();
+    assert_eq!(ordered, ["\"a\"", "10", "9", "[]", r#"{"a":2,"b":1}"#]);
+}
+
+#[test]
+fn converts_to_and_from_value() {
+    let value = json!({"b": [1.5, null, true], "a": "x"});
+    let canonical_value = CanonicalValue::try_from(&value).unwrap();
+    assert_eq!(canonical_value.as_str(), to_string(&value).unwrap());
+    assert_eq!(
+        canonical_value.as_bytes(),
+        canonical_value.to_string().as_bytes()
+    );
+    assert_eq!(Value::try_from(canonical_value.clone()).unwrap(), value);
+    assert_eq!(
+        CanonicalValue::try_from(value).unwrap().into_string(),
+        r#"{"a":"x","b":[1.5,null,true]}"#
+    );
+}
+
+#[test]
+fn parses_strictly() {
+    let value: CanonicalValue = r#"{ "b": 1e0, "a": 2 }"#.parse().unwrap();
+    assert_eq!(value.as_str(), r#"{"a":2,"b":1}"#);
+
+    let error = r#"{"a":1,"a":2}"#.parse::<CanonicalValue>().unwrap_err();
+    assert!(matches!(error, Error::DuplicateKey { .. }));
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record {
+    key: CanonicalValue,
+}
+
+#[test]
+fn deserializes_strictly() {
+    let json = r#"{"key": {"a": [{"b": 1, "b": 2}]}}"#;
+    assert!(serde_json::from_str::<Value>(json).is_ok());
+
+    let error = serde_json::from_str::<Record>(json).err().unwrap();
+    assert!(error
+        .to_string()
+        .starts_with("duplicate object key at `/a/0/b` at line 1"));
+}
+
+#[test]
+fn serializes_as_the_value() {
+    let record: Record = serde_json::from_str(r#"{"key": {"y": 2.0, "x": 1}}"#).unwrap();
+    assert_eq!(record.key.as_str(), r#"{"x":1,"y":2}"#);
+    assert_eq!(
+        serde_json::to_string(&record).unwrap(),
+        r#"{"key":{"x":1,"y":2}}"#
+    );
+}
@@ -0,0 +1,99 @@
+use std::collections::HashMap;
+
+use serde_json::json;
+
+use serde_json_canonicalizer::{to_vec, Canonicalizer, IntegerPolicy};
+
+use common::allocations;
+
+mod common;
+
+fn document(id: usize) -> serde_json::Value {
+    json!({
+        "id": id,
+        "name": format!("record \"{id}\""),
+        "tags": ["b", "a"],
+        "attributes": {"z": [{"y": 1, "x": 2}], "a": "\u{20ac}"},
+        "large": (0..2_000).map(|index| (format!("k{index}"), json!({"v": index}))).collect::<HashMap<_, _>>(),
+    })
+}
+
+#[test]
+fn same_output_as_to_vec() {
+    let mut canonicalizer = Canonicalizer::new();
+    let mut output = Vec::new();
+    for id in 0..3 {
+        let document = document(id);
+        output.clear();
+        canonicalizer
+            .canonicalize_into(&document, &mut output)
+            .unwrap();
+
+        assert_eq!(output, to_vec(&document).unwrap());
+        assert_eq!(
+            canonicalizer.canonicalize(&document).unwrap(),
+            to_vec(&document).unwrap()
+        );
+    }
+}
+
+#[test]
+fn appends_to_output() {
+    let mut output = b"[".to_vec();
+    Canonicalizer::new()
+        .canonicalize_into(&json!({"b": 1, "a": 2}), &mut output)
+        .unwrap();
+
+    assert_eq!(output, br#"[{"a":2,"b":1}"#);
+}
+
+#[test]
+fn steady_state_does_not_allocate() {
+    let documents: Vec<_> = (0..3).map(document).collect();
+    let mut canonicalizer = Canonicalizer::new();
+    let mut output = Vec::new();
+    canonicalizer
+        .canonicalize_into(&documents[0], &mut output)
+        .unwrap();
+    canonicalizer.canonicalize(&documents[0]).unwrap();
+
+    for document in &documents[1..] {
+        assert_ne!(
+            allocations(|| {
+                to_vec(document).unwrap();
+            }),
+            0
+        );
+        assert_eq!(
+            allocations(|| {
+                output.clear();
+                canonicalizer
+                    .canonicalize_into(document, &mut output)
+                    .unwrap();
+            }),
+            0
+        );
+        assert_eq!(
+            allocations(|| {
+                canonicalizer.canonicalize(document).unwrap();
+            }),
+            0
+        );
+    }
+}
+
+#[test]
+fn recovers_from_errors() {
+    let mut canonicalizer = Canonicalizer::new().with_integer_policy(IntegerPolicy::Error);
+    let mut output = b"prefix".to_vec();
+
+    assert!(canonicalizer
+        .canonicalize_into(&json!({"b": {"c": [1, u64::MAX]}, "a": 1}), &mut output)
+        .is_err());
+    assert_eq!(output, b"prefix");
+
+    canonicalizer
+        .canonicalize_into(&json!({"b": 2, "a": 1}), &mut output)
+        .unwrap();
+    assert_eq!(output, br#"prefix{"a":1,"b":2}"#);
+}
@@ -0,0 +1,192 @@
+#![cfg(feature = "cli")]
+
+use std::fs::read_to_string;
+
+use assert_cmd::Command;
+use test_case::test_case;
+
+const ORDER: &str = "tests/resources/cli/order.json";
+const CANONICAL: &str = "tests/resources/cli/order.canonical.json";
+const CHANGED: &str = "tests/resources/cli/order.changed.json";
+
+fn jcs() -> Command {
+    Command::cargo_bin("jcs").unwrap()
+}
+
+fn canonical() -> String {
+    read_to_string(CANONICAL).unwrap()
+}
+
+#[test]
+fn canonicalize_file() {
+    jcs()
+        .args(["canonicalize", ORDER])
+        .assert()
+        .success()
+        .stdout(canonical());
+}
+
+#[test]
+fn canonicalize_stdin() {
+    for args in [&["canonicalize"][..], &["canonicalize", "-"]] {
+        jcs()
+            .args(args)
+            .write_stdin(read_to_string(ORDER).unwrap())
+            .assert()
+            .success()
+            .stdout(canonical());
+    }
+}
+
+#[test]
+fn canonicalize_rfc_example() {
+    jcs()
+        .args(["canonicalize", "tests/resources/rfc_example.input.json"])
+        .assert()
+        .success()
+        .stdout(read_to_string("tests/resources/rfc_example.expected.json").unwrap());
+}
+
+#[test]
+fn canonicalize_invalid_input() {
+    let assert = jcs()
+        .arg("canonicalize")
+        .write_stdin(r#"{"a":"#)
+        .assert()
+        .code(2)
+        .stdout("");
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(
+        stderr.starts_with("jcs: <stdin>: EOF while parsing"),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn missing_file() {
+    let assert = jcs().args(["hash", "missing.json"]).assert().code(2);
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(stderr.starts_with("jcs: missing.json: "), "{stderr}");
+}
+
+#[test]
+fn check_canonical() {
+    jcs()
+        .args(["check", CANONICAL])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn check_not_canonical() {
+    jcs()
+        .args(["check", ORDER])
+        .assert()
+        .code(1)
+        .stdout(format!(
+            "{ORDER}: not canonical: whitespace at byte offset 1\n"
+        ));
+    jcs()
+        .arg("check")
+        .write_stdin(r#"{"b":1,"a":2}"#)
+        .assert()
+        .code(1)
+        .stdout("<stdin>: not canonical: unsorted object key at byte offset 7\n");
+}
+
+#[test_case(&[] => "0b61b4c34ea8a9398b5294482bef8b09d7571fc9a2cee7ed813f34a29f84d590" ; "sha256 hex")]
+#[test_case(&["--encoding", "base64url"] => "C2G0w06oqTmLUpRIK--LCddXH8mizuftgT80op-E1ZA" ; "sha256 base64url")]
+#[test_case(&["-a", "sha512", "-e", "base64url"] => "r5cLK_cK3XHAFm5KcuGXgc20NQQOwsPaXa8qjNlmhzSveyhx3eG0OJIaUru_SFRhP6CE2M0BP_rDK-YMXHiwgA" ; "sha512 base64url")]
+fn hash(args: &[&str]) -> String {
+    let assert = jcs().args(["hash", ORDER]).args(args).assert().success();
+    let stdout = String::from_utf8(assert.get_output().stdout.clone()).unwrap();
+    stdout.strip_suffix('\n').unwrap().to_string()
+}
+
+#[test]
+fn hash_blake3() {
+    let expected = blake3::hash(canonical().as_bytes()).to_hex();
+    jcs()
+        .args(["hash", "--algorithm", "blake3"])
+        .write_stdin(read_to_string(ORDER).unwrap())
+        .assert()
+        .success()
+        .stdout(format!("{expected}\n"));
+}
+
+#[test]
+fn diff_equal() {
+    jcs()
+        .args(["diff", ORDER, CANONICAL])
+        .assert()
+        .success()
+        .stdout("");
+}
+
+#[test]
+fn diff_different() {
+    jcs()
+        .args(["diff", ORDER, CHANGED])
+        .assert()
+        .code(1)
+        .stdout(concat!(
+            "~ /items/1: true -> false\n",
+            "- /items/2: null\n",
+            "- /memo: \"ABC\"\n",
+            "+ /note: \"gift\"\n",
+            "~ /to: \"bob\" -> \"alice\"\n",
+        ));
+}
+
+#[test]
+fn diff_stdin() {
+    jcs()
+        .args(["diff", "-", CANONICAL])
+        .write_stdin(r#"{"a/b":[1.0],"c":{"d~":1}}"#)
+        .assert()
+        .code(1)
+        .stdout(concat!(
+            "- /a~1b: [1]\n",
+            "+ /amount: 1.5\n",
+            "- /c: {\"d~\":1}\n",
+            "+ /items: [1000,true,null]\n",
+            "+ /memo: \"ABC\"\n",
+            "+ /to: \"bob\"\n",
+        ));
+}
+
+#[test]
+fn diff_stdin_twice() {
+    let assert = jcs()
+        .args(["diff", "-", "-"])
+        .write_stdin("{}")
+        .assert()
+        .code(2)
+        .stdout("");
+    let stderr = String::from_utf8(assert.get_output().stderr.clone()).unwrap();
+    assert!(
+        stderr.starts_with(
+            "error: only one of the documents to diff can be read from the standard input"
+        ),
+        "{stderr}"
+    );
+}
+
+#[test]
+fn diff_in_canonical_key_order() {
+    // U+E000 and U+FFFF sort after the surrogate pairs of U+10000 and U+1F600 in UTF-16, but
+    // before them in UTF-8
+    jcs()
+        .args(["diff", "-", "tests/resources/cli/unicode-keys.json"])
+        .write_stdin("{}")
+        .assert()
+        .code(1)
+        .stdout(concat!(
+            "+ /z: 1\n",
+            "+ /\u{10000}: 2\n",
+            "+ /\u{1f600}: 3\n",
+            "+ /\u{e000}: 4\n",
+            "+ /\u{ffff}: 5\n",
+        ));
+}
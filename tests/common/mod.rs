@@ -0,0 +1,112 @@
+//! Helpers shared by the tests and the memory benchmark: a global allocator counting the
+//! allocations and the heap usage of the current thread, proptest strategies of JSON values and
+//! the keys of the signature tests.
+#![allow(dead_code)]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use proptest::prelude::*;
+use serde_json::{Map, Value};
+
+pub struct CountingAllocator;
+
+// signed, memory allocated on one thread may be freed on another one
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+    static CURRENT: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|allocations| allocations.set(allocations.get() + 1));
+        let current = CURRENT.with(|current| {
+            current.set(current.get() + layout.size() as isize);
+            current.get()
+        });
+        PEAK.with(|peak| peak.set(peak.get().max(current)));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        CURRENT.with(|current| current.set(current.get() - layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Returns the number of allocations `f` makes.
+pub fn allocations(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+/// Returns the peak heap usage of `f` above the usage before it was called.
+pub fn peak_usage(f: impl FnOnce()) -> isize {
+    let before = CURRENT.with(Cell::get);
+    PEAK.with(|peak| peak.set(before));
+    f();
+    PEAK.with(Cell::get) - before
+}
+
+/// Strings mixing ASCII, control characters, characters around the surrogate range and ones from
+/// the supplementary planes, which sort differently in UTF-8 and UTF-16.
+pub fn string() -> impl Strategy<Value = String> {
+    prop::collection::vec(
+        prop_oneof![
+            prop::char::range('a', 'e'),
+            prop::char::range('\0', '\x1f'),
+            Just('"'),
+            Just('\\'),
+            Just('/'),
+            prop::char::range('\u{d7ff}', '\u{e001}'),
+            prop::char::range('\u{fffd}', '\u{10002}'),
+            any::<char>(),
+        ],
+        0..6,
+    )
+    .prop_map(|chars| chars.into_iter().collect())
+}
+
+/// Arbitrary JSON values with finite numbers, nested up to four levels deep.
+pub fn value() -> impl Strategy<Value = Value> {
+    let leaf = prop_oneof![
+        Just(Value::Null),
+        any::<bool>().prop_map(Value::from),
+        any::<i64>().prop_map(Value::from),
+        any::<u64>().prop_map(Value::from),
+        any::<f64>()
+            .prop_filter("finite", |number| number.is_finite())
+            .prop_map(Value::from),
+        string().prop_map(Value::from),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            prop::collection::vec(inner.clone(), 0..8).prop_map(Value::from),
+            prop::collection::vec((string(), inner), 0..8).prop_map(|properties| Value::Object(
+                properties.into_iter().collect::<Map<_, _>>()
+            )),
+        ]
+    })
+}
+
+/// Secret key bytes of the signature tests.
+pub const KEY: [u8; 32] = [7; 32];
+
+/// Ed25519 signing key made of [KEY].
+#[cfg(any(feature = "data-integrity", feature = "jsf", feature = "jws"))]
+pub fn ed25519_key() -> ed25519_dalek::SigningKey {
+    ed25519_dalek::SigningKey::from_bytes(&KEY)
+}
+
+/// P-256 signing key made of [KEY].
+#[cfg(any(feature = "data-integrity", feature = "jsf", feature = "jws"))]
+pub fn p256_key() -> p256::ecdsa::SigningKey {
+    p256::ecdsa::SigningKey::from_slice(&KEY).unwrap()
+}
@@ -0,0 +1,249 @@
+#![cfg(feature = "data-integrity")]
+
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+
+use serde_json_canonicalizer::{
+    data_integrity::{create_proof, hash_data, verify_proof, Error, SigningKey, VerifyingKey},
+    to_vec,
+};
+
+use common::{ed25519_key, p256_key};
+
+mod common;
+
+fn resource(name: &str) -> Value {
+    let path = format!("tests/resources/data-integrity/{name}");
+    serde_json::from_slice(&std::fs::read(path).unwrap()).unwrap()
+}
+
+// Test vector of the eddsa-jcs-2022 section of the Data Integrity EdDSA Cryptosuites spec
+fn eddsa_spec_key() -> SigningKey {
+    // multibase encoded Ed25519 secret key with its multicodec prefix
+    let secret = bs58::decode("3u2en7t5LR2WtQH5PfFqMqwVHBeXouLzo6haApm8XHqvjxq")
+        .into_vec()
+        .unwrap();
+    SigningKey::Ed25519(ed25519_dalek::SigningKey::from_bytes(
+        secret[2..].try_into().unwrap(),
+    ))
+}
+
+// Key pair of the examples of the Data Integrity ECDSA Cryptosuites spec. The spec's published
+// ecdsa-jcs-2019 secured document is not vendored: the `ecdsa-jcs-2019-regression` documents are
+// signed by this crate, so they only catch changes to its own output, not deviations from the spec.
+fn ecdsa_spec_key() -> SigningKey {
+    // multibase encoded P-256 secret key with its multicodec prefix
+    let secret = bs58::decode("42twTcNeSYcnqg1FLuSFs2bsGH3ZqbRHFmvS9XMsYhjxvHN")
+        .into_vec()
+        .unwrap();
+    SigningKey::P256(p256::ecdsa::SigningKey::from_slice(&secret[2..]).unwrap())
+}
+
+fn ecdsa_spec_public_key() -> VerifyingKey {
+    // multibase encoded compressed P-256 public key with its multicodec prefix
+    let public = bs58::decode("DnaepBuvsQ8cpsWrVKw8fbpGpvPeNSjVPTWoq6cRqaYzBKVP")
+        .into_vec()
+        .unwrap();
+    VerifyingKey::P256(p256::ecdsa::VerifyingKey::from_sec1_bytes(&public[2..]).unwrap())
+}
+
+fn options() -> Value {
+    json!({
+        "type": "DataIntegrityProof",
+        "created": "2023-02-24T23:36:38Z",
+        "verificationMethod": "https://vc.example/issuers/5678#key-1",
+        "proofPurpose": "assertionMethod"
+    })
+}
+
+fn secure(document: &Value, key: &SigningKey) -> Value {
+    let mut secured = document.clone();
+    secured["proof"] = create_proof(document, &options(), key).unwrap();
+    secured
+}
+
+#[test]
+fn eddsa_jcs_2022_spec_proof() {
+    let proof = create_proof(
+        &resource("eddsa-jcs-2022.unsecured.json"),
+        &resource("eddsa-jcs-2022.proof-options.json"),
+        &eddsa_spec_key(),
+    )
+    .unwrap();
+
+    assert_eq!(resource("eddsa-jcs-2022.secured.json")["proof"], proof);
+}
+
+#[test]
+fn eddsa_jcs_2022_spec_verification() {
+    let key = VerifyingKey::from(&eddsa_spec_key());
+
+    verify_proof(&resource("eddsa-jcs-2022.secured.json"), &key).unwrap();
+}
+
+#[test]
+fn ecdsa_jcs_2019_spec_key() {
+    let (VerifyingKey::P256(public), SigningKey::P256(secret)) =
+        (ecdsa_spec_public_key(), ecdsa_spec_key())
+    else {
+        unreachable!("the spec keys are P-256 keys");
+    };
+
+    assert_eq!(&public, secret.verifying_key());
+}
+
+#[test]
+fn ecdsa_jcs_2019_regression_verification() {
+    let secured = resource("ecdsa-jcs-2019-regression.secured.json");
+    verify_proof(&secured, &ecdsa_spec_public_key()).unwrap();
+
+    let mut unsecured = secured;
+    unsecured.as_object_mut().unwrap().remove("proof");
+    assert_eq!(
+        resource("ecdsa-jcs-2019-regression.unsecured.json"),
+        unsecured
+    );
+}
+
+#[test]
+fn ecdsa_jcs_2019_round_trip_with_spec_key() {
+    let document = resource("ecdsa-jcs-2019-regression.unsecured.json");
+    let mut secured = document.clone();
+    secured["proof"] = create_proof(
+        &document,
+        &resource("ecdsa-jcs-2019-regression.proof-options.json"),
+        &ecdsa_spec_key(),
+    )
+    .unwrap();
+
+    verify_proof(&secured, &ecdsa_spec_public_key()).unwrap();
+}
+
+#[test]
+fn hash_data_concatenates_hashes() {
+    let document = resource("eddsa-jcs-2022.secured.json");
+    let proof = &document["proof"];
+
+    let mut config = proof.clone();
+    config.as_object_mut().unwrap().remove("proofValue");
+    let mut expected = Sha256::digest(to_vec(&config).unwrap()).to_vec();
+    expected.extend(Sha256::digest(
+        to_vec(&resource("eddsa-jcs-2022.unsecured.json")).unwrap(),
+    ));
+
+    assert_eq!(expected, hash_data(&document, proof).unwrap());
+}
+
+#[test]
+fn round_trip() {
+    let document = resource("eddsa-jcs-2022.unsecured.json");
+    for (key, cryptosuite) in [
+        (SigningKey::Ed25519(ed25519_key()), "eddsa-jcs-2022"),
+        (SigningKey::P256(p256_key()), "ecdsa-jcs-2019"),
+    ] {
+        let secured = secure(&document, &key);
+
+        assert_eq!(cryptosuite, secured["proof"]["cryptosuite"]);
+        verify_proof(&secured, &(&key).into()).unwrap();
+    }
+}
+
+#[test]
+fn tampered_document() {
+    for key in [
+        SigningKey::Ed25519(ed25519_key()),
+        SigningKey::P256(p256_key()),
+    ] {
+        let mut secured = secure(&resource("eddsa-jcs-2022.unsecured.json"), &key);
+        secured["name"] = "Forged Credential".into();
+
+        assert!(matches!(
+            verify_proof(&secured, &(&key).into()),
+            Err(Error::VerificationFailed)
+        ));
+    }
+}
+
+#[test]
+fn tampered_proof() {
+    let mut secured = resource("eddsa-jcs-2022.secured.json");
+    secured["proof"]["created"] = "2024-02-24T23:36:38Z".into();
+
+    assert!(matches!(
+        verify_proof(&secured, &(&eddsa_spec_key()).into()),
+        Err(Error::VerificationFailed)
+    ));
+}
+
+#[test]
+fn wrong_key() {
+    let secured = resource("eddsa-jcs-2022.secured.json");
+
+    assert!(matches!(
+        verify_proof(&secured, &(&SigningKey::Ed25519(ed25519_key())).into()),
+        Err(Error::VerificationFailed)
+    ));
+}
+
+#[test]
+fn cryptosuite_mismatch() {
+    let secured = resource("eddsa-jcs-2022.secured.json");
+    assert!(matches!(
+        verify_proof(&secured, &(&SigningKey::P256(p256_key())).into()),
+        Err(Error::CryptosuiteMismatch)
+    ));
+
+    let mut options = options();
+    options["cryptosuite"] = "eddsa-jcs-2022".into();
+    assert!(matches!(
+        create_proof(&json!({}), &options, &SigningKey::P256(p256_key())),
+        Err(Error::CryptosuiteMismatch)
+    ));
+}
+
+#[test]
+fn invalid_proof_value() {
+    let mut secured = resource("eddsa-jcs-2022.secured.json");
+    secured["proof"]["proofValue"] = "uAAAA".into();
+
+    assert!(matches!(
+        verify_proof(&secured, &(&eddsa_spec_key()).into()),
+        Err(Error::InvalidProofValue)
+    ));
+}
+
+#[test]
+fn missing_proof() {
+    let document = resource("eddsa-jcs-2022.unsecured.json");
+
+    assert!(matches!(
+        verify_proof(&document, &(&eddsa_spec_key()).into()),
+        Err(Error::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn document_context_extends_proof_context() {
+    let mut secured = resource("eddsa-jcs-2022.secured.json");
+    secured["@context"]
+        .as_array_mut()
+        .unwrap()
+        .push("https://vc.example/context/v1".into());
+
+    // the additional context is replaced by the one of the proof before hashing
+    verify_proof(&secured, &(&eddsa_spec_key()).into()).unwrap();
+
+    secured["@context"] = json!(["https://www.w3.org/ns/credentials/v2"]);
+    assert!(matches!(
+        verify_proof(&secured, &(&eddsa_spec_key()).into()),
+        Err(Error::InvalidProof(_))
+    ));
+}
+
+#[test]
+fn not_an_object() {
+    assert!(matches!(
+        create_proof(&json!([]), &options(), &SigningKey::Ed25519(ed25519_key())),
+        Err(Error::NotAnObject)
+    ));
+}
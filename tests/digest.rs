@@ -0,0 +1,56 @@
+#![cfg(feature = "digest")]
+
+use serde_json::json;
+use sha2::{Digest, Sha256, Sha512};
+
+use serde_json_canonicalizer::{to_digest, to_hasher, to_vec, to_writer, HashWriter};
+
+#[test]
+fn digest_of_canonical_form() {
+    let value = json!({"b": [1.0, "\u{20ac}"], "a": {"y": null, "x": true}});
+    let canonical = to_vec(&value).unwrap();
+    assert_eq!(
+        Sha256::digest(&canonical),
+        to_digest::<Sha256>(&value).unwrap()
+    );
+    assert_eq!(
+        Sha512::digest(&canonical),
+        to_digest::<Sha512>(&value).unwrap()
+    );
+}
+
+#[test]
+fn rfc_example_sha256() {
+    let input = std::fs::read("tests/resources/rfc_example.input.json").unwrap();
+    let expected = std::fs::read("tests/resources/rfc_example.expected.json").unwrap();
+    let value: serde_json::Value = serde_json::from_slice(&input).unwrap();
+    assert_eq!(
+        Sha256::digest(expected),
+        to_digest::<Sha256>(&value).unwrap()
+    );
+}
+
+#[test]
+fn hasher_with_prefix() {
+    let value = json!({"b": 2, "a": 1});
+    let mut hasher = Sha256::new();
+    hasher.update(b"prefix:");
+    to_hasher(&value, &mut hasher).unwrap();
+    assert_eq!(
+        Sha256::digest(br#"prefix:{"a":1,"b":2}"#),
+        hasher.finalize()
+    );
+}
+
+#[test]
+fn hash_writer() {
+    let mut writer = HashWriter::new(Sha256::new());
+    to_writer(&json!([1, 2]), &mut writer).unwrap();
+    to_writer(&json!({"b": 2, "a": 1}), &mut writer).unwrap();
+    assert_eq!(Sha256::digest(br#"[1,2]{"a":1,"b":2}"#), writer.finalize());
+}
+
+#[test]
+fn errors() {
+    assert!(to_digest::<Sha256>(&f64::NAN).is_err());
+}
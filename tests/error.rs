@@ -0,0 +1,153 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{ser, Serialize, Serializer};
+use serde_json::json;
+use test_case::test_case;
+
+use serde_json_canonicalizer::{
+    pipe_strict, to_vec, Canonicalizer, Error, IntegerPolicy, JcsSerializer,
+};
+
+#[derive(Serialize)]
+struct Order {
+    id: u32,
+    price: f64,
+}
+
+#[derive(Serialize)]
+enum Event {
+    Placed(u32, f64),
+    Shipped { at: f64 },
+    Cancelled(f64),
+}
+
+struct Failing;
+
+impl Serialize for Failing {
+    fn serialize<S: Serializer>(&self, _serializer: S) -> Result<S::Ok, S::Error> {
+        Err(ser::Error::custom("not serializable"))
+    }
+}
+
+fn orders(price: f64) -> BTreeMap<&'static str, Vec<Order>> {
+    let orders = (0..4).map(|id| Order { id, price: 1.5 });
+    let mut orders: Vec<_> = orders.collect();
+    orders[3].price = price;
+    BTreeMap::from([("orders", orders)])
+}
+
+#[test]
+fn non_finite_number_path() {
+    let error = to_vec(&orders(f64::NAN)).unwrap_err();
+    assert!(matches!(&error, Error::NonFiniteNumber { path } if path == "/orders/3/price"));
+    assert_eq!(
+        error.to_string(),
+        "NaN and +/-Infinity are not permitted in JSON at `/orders/3/price`"
+    );
+}
+
+#[test]
+fn root_has_empty_path() {
+    let error = to_vec(&f64::INFINITY).unwrap_err();
+    assert_eq!(error.path(), Some(""));
+    assert_eq!(
+        error.to_string(),
+        "NaN and +/-Infinity are not permitted in JSON"
+    );
+}
+
+#[test_case(Event::Placed(1, f64::NAN) => "/events/0/Placed/1" ; "tuple variant")]
+#[test_case(Event::Shipped { at: f64::NAN } => "/events/0/Shipped/at" ; "struct variant")]
+#[test_case(Event::Cancelled(f64::NAN) => "/events/0/Cancelled" ; "newtype variant")]
+fn variant_paths(event: Event) -> String {
+    let error = to_vec(&BTreeMap::from([("events", [event])])).unwrap_err();
+    error.path().unwrap().to_string()
+}
+
+#[test]
+fn keys_are_escaped() {
+    let value = HashMap::from([("a/b~c", [0.0, f64::NEG_INFINITY])]);
+    let error = to_vec(&value).unwrap_err();
+    assert_eq!(error.path(), Some("/a~1b~0c/1"));
+
+    let value = HashMap::from([("say \"hi\"\n", f64::NAN)]);
+    let error = to_vec(&value).unwrap_err();
+    assert_eq!(error.path(), Some("/say \"hi\"\n"));
+}
+
+#[test]
+fn later_properties_have_their_own_path() {
+    let value = json!({"a": {"b": [1, {"c": 2}]}});
+    let error = to_vec(&(value, BTreeMap::from([("d", f32::NAN)]))).unwrap_err();
+    assert_eq!(error.path(), Some("/1/d"));
+}
+
+#[test]
+fn non_string_key_path() {
+    let value = BTreeMap::from([("a", BTreeMap::from([((), 1)]))]);
+    let error = to_vec(&[value]).unwrap_err();
+    assert!(matches!(&error, Error::NonStringKey { path } if path == "/0/a"));
+    assert_eq!(error.to_string(), "key must be a string at `/0/a`");
+
+    let value = BTreeMap::from([("a", HashMap::from([(vec![1], 1)]))]);
+    let error = to_vec(&value).unwrap_err();
+    assert_eq!(error.path(), Some("/a"));
+}
+
+#[test]
+fn lossy_integer_path() {
+    let mut serializer = JcsSerializer::new(Vec::new()).with_integer_policy(IntegerPolicy::Error);
+    let error = json!({"ids": [1, 9007199254740993u64]})
+        .serialize(&mut serializer)
+        .unwrap_err();
+    match error {
+        Error::LossyInteger { path, value } => {
+            assert_eq!(path, "/ids/1");
+            assert_eq!(value, "9007199254740993");
+        }
+        error => panic!("unexpected error {error}"),
+    }
+
+    let value = BTreeMap::from([("a", BTreeMap::from([(u64::MAX, 1)]))]);
+    let mut canonicalizer = Canonicalizer::new().with_integer_policy(IntegerPolicy::Error);
+    let error = canonicalizer.canonicalize(&value).unwrap_err();
+    assert!(matches!(&error, Error::LossyInteger { path, .. } if path == "/a"));
+}
+
+#[test]
+fn custom_error_path() {
+    let error = to_vec(&("x", [Failing])).unwrap_err();
+    match &error {
+        Error::Custom { path, message } => {
+            assert_eq!(path, "/1/0");
+            assert_eq!(message, "not serializable");
+        }
+        error => panic!("unexpected error {error}"),
+    }
+    assert_eq!(error.to_string(), "not serializable at `/1/0`");
+}
+
+#[test]
+fn duplicate_key_path() {
+    let error = pipe_strict(r#"{"a":[{"b":1,"b":2}]}"#).unwrap_err();
+    assert!(matches!(&error, Error::DuplicateKey { path } if path == "/a/0/b"));
+    assert_eq!(error.to_string(), "duplicate object key at `/a/0/b`");
+
+    let error = pipe_strict(r#"{"a":1"#).unwrap_err();
+    assert!(matches!(error, Error::Json(_)));
+    assert_eq!(error.path(), None);
+}
+
+#[test]
+fn canonicalizer_recovers_after_error() {
+    let mut canonicalizer = Canonicalizer::new();
+    let error = canonicalizer.canonicalize(&orders(f64::NAN)).unwrap_err();
+    assert_eq!(error.path(), Some("/orders/3/price"));
+
+    let error = canonicalizer.canonicalize(&("y", [f64::NAN])).unwrap_err();
+    assert_eq!(error.path(), Some("/1/0"));
+    assert_eq!(
+        canonicalizer.canonicalize(&orders(2.0)).unwrap(),
+        to_vec(&orders(2.0)).unwrap()
+    );
+}
@@ -0,0 +1,205 @@
+#![cfg(feature = "jsf")]
+
+use serde_json::{json, Value};
+use test_case::test_case;
+
+use serde_json_canonicalizer::jsf::{sign, sign_chain, sign_multi, verify, Algorithm, Error};
+
+use common::KEY;
+
+mod common;
+
+const OTHER_KEY: [u8; 32] = [9; 32];
+
+fn document() -> Value {
+    json!({
+        "now": "2019-02-10T11:23:06Z",
+        "escapeMe": "\u{20ac}$\u{000F}\u{000a}A'\u{0042}\u{0022}\u{005c}\\\"/",
+        "numbers": [1e+30, 4.5, 6]
+    })
+}
+
+#[test_case(Algorithm::Ed25519 ; "ed25519")]
+#[test_case(Algorithm::ES256 ; "es256")]
+fn single_signature(algorithm: Algorithm) {
+    let mut document = document();
+    sign(&mut document, &KEY, algorithm).unwrap();
+
+    let public_key = &document["signature"]["publicKey"];
+    assert_eq!(verify(&document).unwrap(), [public_key]);
+}
+
+#[test]
+fn single_signature_layout() {
+    let mut document = document();
+    sign(&mut document, &KEY, Algorithm::Ed25519).unwrap();
+
+    let signature = document["signature"].as_object().unwrap();
+    let mut members: Vec<_> = signature.keys().map(String::as_str).collect();
+    members.sort_unstable();
+    assert_eq!(members, ["algorithm", "publicKey", "value"]);
+    assert_eq!(signature["algorithm"], "Ed25519");
+    assert_eq!(signature["publicKey"]["kty"], "OKP");
+    assert_eq!(signature["publicKey"]["crv"], "Ed25519");
+}
+
+#[test]
+fn ed25519_is_deterministic() {
+    let mut first = document();
+    let mut second = document();
+    sign(&mut first, &KEY, Algorithm::Ed25519).unwrap();
+    sign(&mut second, &KEY, Algorithm::Ed25519).unwrap();
+
+    assert_eq!(first, second);
+}
+
+#[test_case(Algorithm::Ed25519 ; "ed25519")]
+#[test_case(Algorithm::ES256 ; "es256")]
+fn tampered_document(algorithm: Algorithm) {
+    let mut document = document();
+    sign(&mut document, &KEY, algorithm).unwrap();
+    document["now"] = "2019-02-10T11:23:07Z".into();
+
+    assert!(matches!(verify(&document), Err(Error::VerificationFailed)));
+}
+
+#[test]
+fn tampered_signature_object() {
+    let mut document = document();
+    sign(&mut document, &KEY, Algorithm::ES256).unwrap();
+    document["signature"]["keyId"] = "added later".into();
+
+    assert!(matches!(verify(&document), Err(Error::VerificationFailed)));
+}
+
+#[test]
+fn replaced_public_key() {
+    let mut document = document();
+    let mut other = document.clone();
+    sign(&mut document, &KEY, Algorithm::Ed25519).unwrap();
+    sign(&mut other, &OTHER_KEY, Algorithm::Ed25519).unwrap();
+    document["signature"]["publicKey"] = other["signature"]["publicKey"].clone();
+
+    assert!(matches!(verify(&document), Err(Error::VerificationFailed)));
+}
+
+#[test]
+fn already_signed() {
+    let mut document = document();
+    sign(&mut document, &KEY, Algorithm::Ed25519).unwrap();
+
+    assert!(matches!(
+        sign(&mut document, &KEY, Algorithm::Ed25519),
+        Err(Error::InvalidSignature(_))
+    ));
+    assert!(matches!(
+        sign_multi(&mut document, &KEY, Algorithm::Ed25519),
+        Err(Error::InvalidSignature(_))
+    ));
+}
+
+#[test]
+fn multi_signature() {
+    let mut document = document();
+    sign_multi(&mut document, &KEY, Algorithm::Ed25519).unwrap();
+    sign_multi(&mut document, &OTHER_KEY, Algorithm::ES256).unwrap();
+
+    let signers = document["signature"]["signers"].as_array().unwrap();
+    assert_eq!(signers.len(), 2);
+    assert_eq!(
+        verify(&document).unwrap(),
+        [&signers[0]["publicKey"], &signers[1]["publicKey"]]
+    );
+}
+
+#[test]
+fn multi_signature_signers_are_independent() {
+    let mut document = document();
+    sign_multi(&mut document, &KEY, Algorithm::Ed25519).unwrap();
+    sign_multi(&mut document, &OTHER_KEY, Algorithm::Ed25519).unwrap();
+
+    let signers = document["signature"]["signers"].as_array_mut().unwrap();
+    signers.reverse();
+    verify(&document).unwrap();
+
+    document["signature"]["signers"]
+        .as_array_mut()
+        .unwrap()
+        .pop();
+    verify(&document).unwrap();
+}
+
+#[test]
+fn multi_signature_tampered() {
+    let mut document = document();
+    sign_multi(&mut document, &KEY, Algorithm::Ed25519).unwrap();
+    sign_multi(&mut document, &OTHER_KEY, Algorithm::ES256).unwrap();
+    document["numbers"][2] = 7.into();
+
+    assert!(matches!(verify(&document), Err(Error::VerificationFailed)));
+}
+
+#[test]
+fn signature_chain() {
+    let mut document = document();
+    sign_chain(&mut document, &KEY, Algorithm::ES256).unwrap();
+    sign_chain(&mut document, &OTHER_KEY, Algorithm::Ed25519).unwrap();
+
+    let chain = document["signature"]["chain"].as_array().unwrap();
+    assert_eq!(chain.len(), 2);
+    assert_eq!(
+        verify(&document).unwrap(),
+        [&chain[0]["publicKey"], &chain[1]["publicKey"]]
+    );
+}
+
+#[test]
+fn signature_chain_covers_previous_signatures() {
+    let mut document = document();
+    sign_chain(&mut document, &KEY, Algorithm::Ed25519).unwrap();
+    sign_chain(&mut document, &OTHER_KEY, Algorithm::Ed25519).unwrap();
+
+    // removing the first signature invalidates the second one
+    document["signature"]["chain"]
+        .as_array_mut()
+        .unwrap()
+        .remove(0);
+    assert!(matches!(verify(&document), Err(Error::VerificationFailed)));
+
+    // but dropping the last one leaves a valid chain
+    let mut document = self::document();
+    sign_chain(&mut document, &KEY, Algorithm::Ed25519).unwrap();
+    sign_chain(&mut document, &OTHER_KEY, Algorithm::Ed25519).unwrap();
+    document["signature"]["chain"].as_array_mut().unwrap().pop();
+    verify(&document).unwrap();
+}
+
+#[test]
+fn invalid_keys() {
+    assert!(matches!(
+        sign(&mut document(), &KEY[..31], Algorithm::Ed25519),
+        Err(Error::InvalidKey)
+    ));
+    assert!(matches!(
+        sign(&mut document(), &[0; 32], Algorithm::ES256),
+        Err(Error::InvalidKey)
+    ));
+
+    let mut document = document();
+    sign(&mut document, &KEY, Algorithm::Ed25519).unwrap();
+    document["signature"]["algorithm"] = "ES256".into();
+    assert!(matches!(verify(&document), Err(Error::InvalidKey)));
+}
+
+#[test_case(json!([]), Error::NotAnObject ; "not an object")]
+#[test_case(json!({}), Error::InvalidSignature("document is not signed") ; "not signed")]
+#[test_case(json!({"signature": {"signers": []}}), Error::InvalidSignature("signers is not a non-empty array") ; "no signers")]
+#[test_case(json!({"signature": {"algorithm": "RS256", "value": ""}}), Error::UnsupportedAlgorithm("RS256".into()) ; "unsupported algorithm")]
+#[test_case(json!({"signature": {"algorithm": "Ed25519"}}), Error::InvalidSignature("signature has no publicKey") ; "no public key")]
+#[test_case(json!({"signature": {"excludes": ["a"]}}), Error::InvalidSignature("excludes is not supported") ; "excludes")]
+fn malformed(document: Value, expected: Error) {
+    assert_eq!(
+        verify(&document).unwrap_err().to_string(),
+        expected.to_string()
+    );
+}
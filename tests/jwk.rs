@@ -0,0 +1,94 @@
+#![cfg(feature = "jwk")]
+
+use serde::Serialize;
+use serde_json::json;
+use test_case::test_case;
+
+use serde_json_canonicalizer::jwk::{thumbprint, thumbprint_uri, Error};
+
+// RFC 7638 section 3.1
+fn rfc7638_key() -> serde_json::Value {
+    json!({
+        "kty": "RSA",
+        "n": "0vx7agoebGcQSuuPiLJXZptN9nndrQmbXEps2aiAFbWhM78LhWx4cbbfAAtVT86zwu1RK7aPFFxuhDR1L6tSoc_BJECPebWKRXjBZCiFV4n3oknjhMstn64tZ_2W-5JsGY4Hc5n9yBXArwl93lqt7_RN5w6Cf0h4QyQ5v-65YGjQR0_FDW2QvzqY368QQMicAtaSqzs8KJZgnYb9c7d0zgdAZHzu6qMQvRL5hajrn1n91CbOpbISD08qNLyrdkt-bFTWhAI4vMQFh6WeZu0fM4lFd2NcRwr3XPksINHaQ-G_xBniIqbw0Ls1jF44-csFCur-kEgU8awapJzKnqDKgw",
+        "e": "AQAB",
+        "alg": "RS256",
+        "kid": "2011-04-29"
+    })
+}
+
+#[test]
+fn rfc7638_example() {
+    assert_eq!(
+        "NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs",
+        thumbprint(&rfc7638_key()).unwrap()
+    );
+}
+
+// RFC 9278 section 3
+#[test]
+fn rfc9278_example() {
+    assert_eq!(
+        "urn:ietf:params:oauth:jwk-thumbprint:sha-256:NzbLsXh8uDCcd-6MNwXF4W_7noWXFZAfHkxZsRGC9Xs",
+        thumbprint_uri(&rfc7638_key()).unwrap()
+    );
+}
+
+// RFC 8037 appendix A.3
+#[test]
+fn rfc8037_okp_example() {
+    let jwk = json!({
+        "kty": "OKP",
+        "crv": "Ed25519",
+        "d": "nWGxne_9WmC6hEr0kuwsxERJxWl7MmkZcDusAxyuf2A",
+        "x": "11qYAYKxCrfVS_7TyWQHOg7hcvPapiMlrwIaaPcHURo"
+    });
+    assert_eq!(
+        "kPrK_qmxVWaYVA9wwBF6Iuo3vVzz7TxHCTwXBygrS4k",
+        thumbprint(&jwk).unwrap()
+    );
+}
+
+#[test]
+fn typed_key() {
+    #[derive(Serialize)]
+    struct EcKey {
+        kty: &'static str,
+        crv: &'static str,
+        x: &'static str,
+        y: &'static str,
+        #[serde(rename = "use")]
+        key_use: &'static str,
+    }
+
+    let typed = EcKey {
+        kty: "EC",
+        crv: "P-256",
+        x: "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+        y: "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0",
+        key_use: "sig",
+    };
+    let value = json!({
+        "kty": "EC",
+        "crv": "P-256",
+        "x": "f83OJ3D2xF1Bg8vub9tLe1gHMzV76e8Tus9uPHvRVEU",
+        "y": "x_FEzRu9m36HLN_tue659LNpXW6pCyStikYjKIWI5a0",
+    });
+    assert_eq!(thumbprint(&value).unwrap(), thumbprint(&typed).unwrap());
+}
+
+#[test]
+fn symmetric_key() {
+    let jwk = json!({"kty": "oct", "k": "AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow", "alg": "HS256"});
+    let expected = thumbprint(&json!({"k": jwk["k"], "kty": "oct"})).unwrap();
+    assert_eq!(expected, thumbprint(&jwk).unwrap());
+}
+
+#[test_case(json!([]) => matches Error::NotAnObject ; "not an object")]
+#[test_case(json!({"n": "x", "e": "AQAB"}) => matches Error::MissingMember("kty") ; "no key type")]
+#[test_case(json!({"kty": "DSA"}) => matches Error::UnsupportedKeyType(_) ; "unsupported key type")]
+#[test_case(json!({"kty": "RSA", "n": "x"}) => matches Error::MissingMember("e") ; "missing member")]
+#[test_case(json!({"kty": "OKP", "crv": "Ed25519", "x": 1}) => matches Error::MissingMember("x") ; "member not a string")]
+fn errors(jwk: serde_json::Value) -> Error {
+    thumbprint(&jwk).unwrap_err()
+}
@@ -0,0 +1,146 @@
+#![cfg(feature = "jws")]
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use hmac::{Hmac, Mac};
+use serde::Serialize;
+use serde_json::json;
+use sha2::Sha256;
+use test_case::test_case;
+
+use serde_json_canonicalizer::jws::{sign, verify, Error, SigningKey, VerifyingKey};
+
+use common::{ed25519_key, p256_key};
+
+mod common;
+
+// RFC 7797 section 4.2
+const RFC7797_HEADER: &str = "eyJhbGciOiJIUzI1NiIsImI2NCI6ZmFsc2UsImNyaXQiOlsiYjY0Il19";
+
+// RFC 7515 appendix A.1
+fn rfc7515_hmac_key() -> SigningKey {
+    SigningKey::HS256(
+        URL_SAFE_NO_PAD
+            .decode("AyM1SysPpbyDfgZld3umj1qzKObwVMkoqQ-EstJQLr_T-1qS0gZH75aKtMN3Yj0iPS4hcgUuTwjAzZr1Z9CAow")
+            .unwrap(),
+    )
+}
+
+fn header(jws: &str) -> serde_json::Value {
+    let encoded = jws.split('.').next().unwrap();
+    serde_json::from_slice(&URL_SAFE_NO_PAD.decode(encoded).unwrap()).unwrap()
+}
+
+fn encode_header(header: serde_json::Value) -> String {
+    URL_SAFE_NO_PAD.encode(header.to_string())
+}
+
+#[derive(Serialize)]
+struct Payment {
+    to: &'static str,
+    amount: f64,
+}
+
+#[test]
+fn rfc7797_header_and_signing_input() {
+    let key = rfc7515_hmac_key();
+    let payload = json!({"to": "\u{20ac}", "amount": 2e-3});
+    let jws = sign(&payload, &key).unwrap();
+
+    let SigningKey::HS256(secret) = &key else {
+        unreachable!()
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).unwrap();
+    mac.update(format!("{RFC7797_HEADER}.{{\"amount\":0.002,\"to\":\"\u{20ac}\"}}").as_bytes());
+    let expected_signature = URL_SAFE_NO_PAD.encode(mac.finalize().into_bytes());
+
+    assert_eq!(format!("{RFC7797_HEADER}..{expected_signature}"), jws);
+}
+
+#[test_case(rfc7515_hmac_key(), "HS256" ; "hs256")]
+#[test_case(SigningKey::ES256(p256_key()), "ES256" ; "es256")]
+#[test_case(SigningKey::EdDSA(ed25519_key()), "EdDSA" ; "eddsa")]
+fn round_trip(key: SigningKey, alg: &str) {
+    let jws = sign(
+        &Payment {
+            to: "alice",
+            amount: 10.5,
+        },
+        &key,
+    )
+    .unwrap();
+
+    assert_eq!(
+        json!({"alg": alg, "b64": false, "crit": ["b64"]}),
+        header(&jws)
+    );
+    // any representation of the same payload verifies
+    verify(
+        &jws,
+        &json!({"amount": 10.5, "to": "alice"}),
+        &(&key).into(),
+    )
+    .unwrap();
+}
+
+#[test_case(rfc7515_hmac_key() ; "hs256")]
+#[test_case(SigningKey::ES256(p256_key()) ; "es256")]
+#[test_case(SigningKey::EdDSA(ed25519_key()) ; "eddsa")]
+fn tampered_payload(key: SigningKey) {
+    let jws = sign(&json!({"amount": 10.5}), &key).unwrap();
+
+    assert!(matches!(
+        verify(&jws, &json!({"amount": 100.5}), &(&key).into()),
+        Err(Error::VerificationFailed)
+    ));
+}
+
+#[test]
+fn wrong_key() {
+    let jws = sign(&json!({}), &rfc7515_hmac_key()).unwrap();
+
+    assert!(matches!(
+        verify(&jws, &json!({}), &VerifyingKey::HS256(b"other".to_vec())),
+        Err(Error::VerificationFailed)
+    ));
+}
+
+#[test]
+fn algorithm_mismatch() {
+    let jws = sign(&json!({}), &SigningKey::EdDSA(ed25519_key())).unwrap();
+
+    assert!(matches!(
+        verify(&jws, &json!({}), &(&SigningKey::ES256(p256_key())).into()),
+        Err(Error::AlgorithmMismatch)
+    ));
+}
+
+#[test]
+fn attached_payload() {
+    let jws = sign(&json!({}), &SigningKey::EdDSA(ed25519_key())).unwrap();
+    let attached = jws.replace("..", ".e30.");
+
+    assert!(matches!(
+        verify(
+            &attached,
+            &json!({}),
+            &(&SigningKey::EdDSA(ed25519_key())).into()
+        ),
+        Err(Error::InvalidFormat(_))
+    ));
+}
+
+#[test_case(json!({"alg": "HS256"}) ; "encoded payload")]
+#[test_case(json!({"alg": "HS256", "b64": true, "crit": ["b64"]}) ; "b64 true")]
+#[test_case(json!({"alg": "HS256", "b64": false}) ; "b64 not critical")]
+#[test_case(json!({"alg": "HS256", "b64": false, "crit": ["b64", "exp"]}) ; "unknown critical extension")]
+#[test_case(json!({"b64": false, "crit": ["b64"]}) ; "no alg")]
+fn unsupported_header(header: serde_json::Value) {
+    let jws = sign(&json!({}), &rfc7515_hmac_key()).unwrap();
+    let signature = jws.split('.').nth(2).unwrap();
+    let jws = format!("{}..{signature}", encode_header(header));
+
+    assert!(matches!(
+        verify(&jws, &json!({}), &(&rfc7515_hmac_key()).into()),
+        Err(Error::InvalidFormat(_))
+    ));
+}
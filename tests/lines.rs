@@ -0,0 +1,122 @@
+#![cfg(feature = "std")]
+
+use std::io::{self, Write};
+
+use serde_json_canonicalizer::{pipe_lines, Error, LinePipe};
+
+fn canonicalize_lines(input: &str) -> String {
+    let mut output = Vec::new();
+    pipe_lines(input.as_bytes(), &mut output).unwrap();
+    String::from_utf8(output).unwrap()
+}
+
+#[test]
+fn canonicalizes_each_line() {
+    let input = concat!(
+        "{\"event\": \"login\", \"at\": 1.7e9, \"user\": {\"name\": \"bob\", \"id\": 7}}\n",
+        "[3, 2.50, \"\\u0041\"]\n",
+        "{\"a\": 1, \"a\": 2}\n",
+        "null\n",
+    );
+    let expected = concat!(
+        "{\"at\":1700000000,\"event\":\"login\",\"user\":{\"id\":7,\"name\":\"bob\"}}\n",
+        "[3,2.5,\"A\"]\n",
+        "{\"a\":2}\n",
+        "null\n",
+    );
+    assert_eq!(canonicalize_lines(input), expected);
+}
+
+#[test]
+fn preserves_line_boundaries() {
+    assert_eq!(canonicalize_lines(""), "");
+    assert_eq!(canonicalize_lines("1.0"), "1");
+    assert_eq!(canonicalize_lines("\n\n"), "\n\n");
+    assert_eq!(canonicalize_lines("{ }\r\n \t\r\n[ ]"), "{}\r\n\r\n[]");
+    assert_eq!(
+        canonicalize_lines("\n  {\"b\":0,\"a\":0}  \n\n"),
+        "\n{\"a\":0,\"b\":0}\n\n"
+    );
+}
+
+#[test]
+fn stops_at_first_invalid_line() {
+    let input = "[1.0]\n\n{\"a\": }\n[2.0]\n";
+    let mut output = Vec::new();
+    let error = pipe_lines(input.as_bytes(), &mut output).unwrap_err();
+    assert_eq!(error.line(), 3);
+    assert!(matches!(error.error(), Error::Json(_)));
+    assert!(error.to_string().starts_with("line 3: expected value"));
+    assert_eq!(output, b"[1]\n\n");
+}
+
+#[test]
+fn multiple_documents_on_a_line_are_invalid() {
+    let error = pipe_lines(&b"1 2\n"[..], io::sink()).unwrap_err();
+    assert_eq!(error.line(), 1);
+    assert!(matches!(error.into_error(), Error::Json(_)));
+}
+
+#[test]
+fn skips_invalid_lines() {
+    let input = "{\"b\":1,\"a\":2}\n{\"a\":\n[1e400]\n[\"ok\"]\n";
+    let mut output = Vec::new();
+    let errors = LinePipe::new()
+        .skip_invalid(true)
+        .pipe(input.as_bytes(), &mut output)
+        .unwrap();
+    assert_eq!(output, b"{\"a\":2,\"b\":1}\n\n\n[\"ok\"]\n");
+    let lines: Vec<_> = errors.iter().map(|error| error.line()).collect();
+    assert_eq!(lines, [2, 3]);
+}
+
+struct FailingWriter;
+
+impl Write for FailingWriter {
+    fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+        Err(io::ErrorKind::BrokenPipe.into())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn write_errors_are_not_skipped() {
+    let error = LinePipe::new()
+        .skip_invalid(true)
+        .pipe(&b"\n[]\n"[..], FailingWriter)
+        .unwrap_err();
+    assert_eq!(error.line(), 1);
+    assert!(matches!(error.error(), Error::Io(error) if error.kind() == io::ErrorKind::BrokenPipe));
+}
+
+#[cfg(feature = "digest")]
+#[test]
+fn prefixes_records_with_their_hash() {
+    use sha2::{Digest, Sha256, Sha512};
+
+    let input = "{\"b\": [1.0], \"a\": null}\n\n[true]";
+    let mut output = Vec::new();
+    LinePipe::new()
+        .with_digest::<Sha256>()
+        .pipe(input.as_bytes(), &mut output)
+        .unwrap();
+    let hash = |json: &str| format!("{:x}", Sha256::digest(json));
+    let expected = format!(
+        "{}\t{{\"a\":null,\"b\":[1]}}\n\n{}\t[true]",
+        hash(r#"{"a":null,"b":[1]}"#),
+        hash("[true]")
+    );
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+
+    let mut output = Vec::new();
+    LinePipe::new()
+        .with_digest::<Sha512>()
+        .skip_invalid(true)
+        .pipe(&b"nul\n1\n"[..], &mut output)
+        .unwrap();
+    let expected = format!("\n{:x}\t1\n", Sha512::digest("1"));
+    assert_eq!(String::from_utf8(output).unwrap(), expected);
+}
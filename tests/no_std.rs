@@ -0,0 +1,37 @@
+use std::process::Command;
+
+/// Builds `no-std-check` for `thumbv7em-none-eabi`, a target without the standard library.
+///
+/// Needs the target installed: `rustup target add thumbv7em-none-eabi`.
+#[test]
+#[ignore = "needs the thumbv7em-none-eabi target"]
+fn builds_without_std() {
+    let status = Command::new(env!("CARGO"))
+        .arg("build")
+        .current_dir(concat!(env!("CARGO_MANIFEST_DIR"), "/no-std-check"))
+        .status()
+        .unwrap();
+
+    assert!(status.success());
+}
+
+/// Builds the library for `thumbv7em-none-eabi` with each of the features that work without the
+/// standard library enabled on its own, as they must enable `alloc` themselves.
+///
+/// Needs the target installed: `rustup target add thumbv7em-none-eabi`.
+#[test]
+#[ignore = "needs the thumbv7em-none-eabi target"]
+fn features_build_without_std() {
+    for features in ["alloc", "base64", "digest"] {
+        let status = Command::new(env!("CARGO"))
+            .args(["build", "--lib", "--no-default-features"])
+            .args(["--features", features])
+            .args(["--target", "thumbv7em-none-eabi"])
+            .args(["--target-dir", "target/no-std"])
+            .current_dir(env!("CARGO_MANIFEST_DIR"))
+            .status()
+            .unwrap();
+
+        assert!(status.success(), "features: {features}");
+    }
+}
@@ -1,11 +1,12 @@
-use std::io;
-
 use serde::Serialize;
 use test_case::case;
 
-use serde_json_canonicalizer::to_vec;
+use serde_json_canonicalizer::{
+    pipe_strict_with, pipe_with, to_string_with, to_vec, to_vec_with, to_writer_with, Error,
+    IntegerPolicy, JcsSerializer,
+};
 
-fn value_to_string<V: Serialize>(value: V) -> io::Result<String> {
+fn value_to_string<V: Serialize>(value: V) -> Result<String, Error> {
     let buffer = to_vec(&value)?;
     String::from_utf8(buffer).map_err(|_| unreachable!())
 }
@@ -206,3 +207,112 @@ fn test_issue_5() {
 
     assert_eq!(json_string, r#"{"number":300}"#);
 }
+
+fn value_to_string_with_policy<V: Serialize>(
+    value: V,
+    policy: IntegerPolicy,
+) -> Result<String, Error> {
+    let mut serializer = JcsSerializer::new(Vec::new()).with_integer_policy(policy);
+    value.serialize(&mut serializer)?;
+    String::from_utf8(serializer.into_inner()).map_err(|_| unreachable!())
+}
+
+#[case(9007199254740991u64 => "9007199254740991" ; "max safe integer")]
+#[case(9007199254740992u64 => "\"9007199254740992\"" ; "max safe integer + 1")]
+#[case(9007199254740993u64 => "\"9007199254740993\"" ; "not representable")]
+#[case(u64::MAX => "\"18446744073709551615\"" ; "u64::MAX")]
+fn integer_policy_string_u64(number: u64) -> String {
+    value_to_string_with_policy(number, IntegerPolicy::String).unwrap()
+}
+
+#[case(-9007199254740991i64 => "-9007199254740991" ; "min safe integer")]
+#[case(-9007199254740993i64 => "\"-9007199254740993\"" ; "not representable")]
+#[case(i64::MIN => "\"-9223372036854775808\"" ; "i64::MIN")]
+fn integer_policy_string_i64(number: i64) -> String {
+    value_to_string_with_policy(number, IntegerPolicy::String).unwrap()
+}
+
+#[case(u128::MAX => "\"340282366920938463463374607431768211455\"" ; "u128::MAX")]
+fn integer_policy_string_u128(number: u128) -> String {
+    value_to_string_with_policy(number, IntegerPolicy::String).unwrap()
+}
+
+#[case(i128::MIN => "\"-170141183460469231731687303715884105728\"" ; "i128::MIN")]
+fn integer_policy_string_i128(number: i128) -> String {
+    value_to_string_with_policy(number, IntegerPolicy::String).unwrap()
+}
+
+#[test]
+fn integer_policy_error() {
+    let policy = IntegerPolicy::Error;
+    assert_eq!(
+        "9007199254740991",
+        value_to_string_with_policy(9007199254740991u64, policy).unwrap()
+    );
+    assert_eq!(
+        "-9007199254740991",
+        value_to_string_with_policy(-9007199254740991i128, policy).unwrap()
+    );
+    assert!(value_to_string_with_policy(9007199254740992u64, policy).is_err());
+    assert!(value_to_string_with_policy(vec![-9007199254740993i64], policy).is_err());
+    assert!(value_to_string_with_policy(u128::MAX, policy).is_err());
+    assert!(
+        value_to_string_with_policy(serde_json::json!({"id": 9007199254740993u64}), policy)
+            .is_err()
+    );
+    // floats are doubles already, nothing is lost
+    assert_eq!(
+        "9007199254740992",
+        value_to_string_with_policy(9007199254740992f64, policy).unwrap()
+    );
+}
+
+#[test]
+fn integer_policy_keys() {
+    let map = std::collections::BTreeMap::from([(9007199254740993u64, 1), (1, 2)]);
+    assert_eq!(
+        r#"{"1":2,"9007199254740992":1}"#,
+        value_to_string_with_policy(&map, IntegerPolicy::Lossy).unwrap()
+    );
+    assert_eq!(
+        r#"{"1":2,"9007199254740993":1}"#,
+        value_to_string_with_policy(&map, IntegerPolicy::String).unwrap()
+    );
+    assert!(value_to_string_with_policy(&map, IntegerPolicy::Error).is_err());
+}
+
+#[test]
+fn integer_policy_default_is_lossy() {
+    assert_eq!(IntegerPolicy::Lossy, IntegerPolicy::default());
+    assert_eq!(
+        "9007199254740992",
+        value_to_string_with_policy(9007199254740993u64, IntegerPolicy::default()).unwrap()
+    );
+}
+
+#[test]
+fn integer_policy_of_convenience_functions() {
+    let value = serde_json::json!({"id": 9007199254740993u64});
+    let json = r#"{"id": 9007199254740993}"#;
+
+    assert_eq!(
+        to_vec_with(&value, IntegerPolicy::Lossy).unwrap(),
+        br#"{"id":9007199254740992}"#
+    );
+    assert_eq!(
+        to_string_with(&value, IntegerPolicy::String).unwrap(),
+        r#"{"id":"9007199254740993"}"#
+    );
+    let mut output = Vec::new();
+    let error = to_writer_with(&value, &mut output, IntegerPolicy::Error).unwrap_err();
+    assert!(matches!(&error, Error::LossyInteger { path, .. } if path == "/id"));
+
+    assert!(matches!(
+        pipe_with(json, IntegerPolicy::Error),
+        Err(Error::LossyInteger { .. })
+    ));
+    assert_eq!(
+        pipe_strict_with(json, IntegerPolicy::String).unwrap(),
+        r#"{"id":"9007199254740993"}"#
+    );
+}
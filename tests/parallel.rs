@@ -0,0 +1,66 @@
+#![cfg(feature = "rayon")]
+
+use proptest::prelude::*;
+use rayon::ThreadPoolBuilder;
+use serde_json::{json, Value};
+
+use serde_json_canonicalizer::{canonicalize_batch, par_to_vec, to_vec, Error};
+
+use common::value;
+
+mod common;
+
+proptest! {
+    #[test]
+    fn same_bytes_as_sequential(values in prop::collection::vec(value(), 0..32)) {
+        // more threads than cores, splitting the array into more chunks
+        let pool = ThreadPoolBuilder::new().num_threads(3).build().unwrap();
+        let parallel = pool.install(|| par_to_vec(&values)).unwrap();
+        prop_assert_eq!(parallel, to_vec(&values).unwrap());
+    }
+
+    #[test]
+    fn batch_same_bytes_as_sequential(values in prop::collection::vec(value(), 0..16)) {
+        let batch = canonicalize_batch(&values);
+        prop_assert_eq!(batch.len(), values.len());
+        for (canonical, value) in batch.into_iter().zip(&values) {
+            prop_assert_eq!(canonical.unwrap(), to_vec(value).unwrap());
+        }
+    }
+}
+
+#[test]
+fn empty_and_single_element() {
+    assert_eq!(par_to_vec::<Value>(&[]).unwrap(), b"[]");
+    assert_eq!(
+        par_to_vec(&[json!({"b": 1.0, "a": []})]).unwrap(),
+        br#"[{"a":[],"b":1}]"#
+    );
+    assert!(canonicalize_batch::<Value>(&[]).is_empty());
+}
+
+#[test]
+fn many_elements_on_many_threads() {
+    let records: Vec<_> = (0..10_000)
+        .map(|id| json!({"id": id, "score": id as f64 / 7.0, "tags": ["x", id.to_string()]}))
+        .collect();
+    let pool = ThreadPoolBuilder::new().num_threads(8).build().unwrap();
+    let parallel = pool.install(|| par_to_vec(&records)).unwrap();
+    assert_eq!(parallel, to_vec(&records).unwrap());
+}
+
+#[test]
+fn first_error_has_the_element_path() {
+    let mut values = vec![vec![1.5]; 1000];
+    values[700] = vec![f64::NAN];
+    values[300] = vec![1.0, f64::INFINITY];
+
+    let pool = ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+    let error = pool.install(|| par_to_vec(&values)).unwrap_err();
+    assert!(matches!(&error, Error::NonFiniteNumber { path } if path == "/300/1"));
+
+    let batch = canonicalize_batch(&values);
+    assert_eq!(batch[0].as_ref().unwrap(), b"[1.5]");
+    assert_eq!(batch[300].as_ref().unwrap_err().path(), Some("/1"));
+    assert_eq!(batch[700].as_ref().unwrap_err().path(), Some("/0"));
+}
@@ -0,0 +1,177 @@
+#![cfg(feature = "std")]
+
+use std::{
+    fs::read_dir,
+    io::{self, Read, Write},
+};
+
+use serde_json_canonicalizer::{pipe, pipe_reader, Error};
+
+use common::peak_usage;
+
+mod common;
+
+fn record(index: usize) -> String {
+    format!(
+        r#"{{"name": "record \"{index}\"", "id": {index}, "\u20ac": [1e1, -0, {index}.5, true, null],
+        "nested": {{"z": {{"y\n": [], "x": {{}}}}, "a": "\ud83d\ude00"}}, "id": "duplicate {index}"}}"#
+    )
+}
+
+/// Generates a top-level array of records while it is being read.
+struct Records {
+    count: usize,
+    next: usize,
+    pending: Vec<u8>,
+    position: usize,
+}
+
+impl Records {
+    fn new(count: usize) -> Self {
+        Self {
+            count,
+            next: 0,
+            pending: b"[".to_vec(),
+            position: 0,
+        }
+    }
+}
+
+impl Read for Records {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.position == self.pending.len() {
+            self.pending.clear();
+            self.position = 0;
+            match self.next {
+                next if next < self.count => {
+                    if next > 0 {
+                        self.pending.push(b',');
+                    }
+                    self.pending.extend(record(next).bytes());
+                }
+                next if next == self.count => self.pending.push(b']'),
+                _ => return Ok(0),
+            }
+            self.next += 1;
+        }
+        let count = buf.len().min(self.pending.len() - self.position);
+        buf[..count].copy_from_slice(&self.pending[self.position..self.position + count]);
+        self.position += count;
+        Ok(count)
+    }
+}
+
+/// Counts the bytes written.
+#[derive(Default)]
+struct Counter(usize);
+
+impl Write for Counter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+fn pipe_bytes(input: &[u8]) -> Result<String, Error> {
+    let mut output = Vec::new();
+    pipe_reader(input, &mut output)?;
+    Ok(String::from_utf8(output).unwrap())
+}
+
+#[test]
+fn reference_testdata() {
+    for file in read_dir("tests/resources/testdata/input").unwrap() {
+        let path = file.unwrap().path();
+        let input = std::fs::read(&path).unwrap();
+        let expected = std::fs::read_to_string(
+            path.parent()
+                .unwrap()
+                .with_file_name("output")
+                .join(path.file_name().unwrap()),
+        )
+        .unwrap();
+
+        assert_eq!(pipe_bytes(&input).unwrap(), expected, "{path:?}");
+    }
+}
+
+#[test]
+fn same_output_as_pipe() {
+    let mut input = String::new();
+    Records::new(10_000).read_to_string(&mut input).unwrap();
+    let object = format!(
+        "{{{}}}",
+        (0..1_000)
+            .map(|index| format!(r#""k{}": {}"#, 1_000 - index, record(index)))
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+
+    for input in [input, object] {
+        assert_eq!(pipe_bytes(input.as_bytes()).unwrap(), pipe(&input).unwrap());
+    }
+}
+
+#[test]
+fn scalars_and_whitespace() {
+    for input in [
+        " 1.50 ",
+        "\"\\u0041\\/\"",
+        "null",
+        "[ ]",
+        "{ }",
+        "[[], {}, [{}]]",
+    ] {
+        assert_eq!(pipe_bytes(input.as_bytes()).unwrap(), pipe(input).unwrap());
+    }
+}
+
+#[test]
+fn duplicate_keys_keep_the_last_value() {
+    let input = r#"{"b": 1, "a": {"c": 1, "c": [2]}, "b": 3}"#;
+
+    assert_eq!(
+        pipe_bytes(input.as_bytes()).unwrap(),
+        r#"{"a":{"c":[2]},"b":3}"#
+    );
+    assert_eq!(pipe(input).unwrap(), r#"{"a":{"c":[2]},"b":3}"#);
+}
+
+#[test]
+fn malformed_input() {
+    for input in ["", "[1,", "{\"a\" 1}", "[1] [2]", "1e400", "\"\\ud800\""] {
+        assert!(pipe_bytes(input.as_bytes()).is_err(), "{input}");
+    }
+}
+
+#[test]
+fn write_errors_are_io_errors() {
+    struct Failing;
+
+    impl Write for Failing {
+        fn write(&mut self, _buf: &[u8]) -> io::Result<usize> {
+            Err(io::Error::new(io::ErrorKind::BrokenPipe, "closed"))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let error = pipe_reader(&b"[{\"a\": 1}]"[..], Failing).unwrap_err();
+    assert!(matches!(error, Error::Io(error) if error.kind() == io::ErrorKind::BrokenPipe));
+}
+
+#[test]
+fn memory_does_not_grow_with_top_level_arrays() {
+    let mut written = Counter::default();
+    let peak = peak_usage(|| pipe_reader(Records::new(100_000), &mut written).unwrap());
+
+    // about 12 MB of output, with memory bounded by the size of a single record
+    assert!(written.0 > 10_000_000);
+    assert!(peak < 256 * 1024, "peak heap usage {peak}");
+}
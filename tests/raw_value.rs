@@ -0,0 +1,85 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+use serde_json::value::RawValue;
+
+use serde_json_canonicalizer::{to_string, to_vec, value_to_vec, Canonicalizer, Error};
+
+#[derive(Serialize)]
+struct Envelope<'a> {
+    kind: &'static str,
+    payload: &'a RawValue,
+    id: u32,
+}
+
+fn raw(json: &str) -> Box<RawValue> {
+    RawValue::from_string(json.to_string()).unwrap()
+}
+
+#[test]
+fn raw_value_is_canonicalized() {
+    let payload = raw(r#" { "b" : [ 1.0, 12e1, "A" ], "a" : null } "#);
+    let envelope = Envelope {
+        kind: "order",
+        payload: &payload,
+        id: 7,
+    };
+
+    assert_eq!(
+        to_string(&envelope).unwrap(),
+        r#"{"id":7,"kind":"order","payload":{"a":null,"b":[1,120,"A"]}}"#
+    );
+}
+
+#[test]
+fn top_level_raw_value() {
+    assert_eq!(
+        to_vec(&raw(r#"{"b":2,"a":1}"#)).unwrap(),
+        br#"{"a":1,"b":2}"#
+    );
+    assert_eq!(to_vec(&raw("-0.0")).unwrap(), b"0");
+}
+
+#[test]
+fn raw_values_nested_in_sorted_objects() {
+    let text = r#"{"z": {"y": 1, "x": [{"d": 4, "c": 3}]}, "a": "é", "m": []}"#;
+    let payload = raw(text);
+    let value = HashMap::from([
+        ("outer", HashMap::from([("b", &payload), ("a", &payload)])),
+        ("inner", HashMap::from([("only", &payload)])),
+    ]);
+
+    assert_eq!(
+        to_string(&value).unwrap(),
+        r#"{"inner":{"only":{"a":"é","m":[],"z":{"x":[{"c":3,"d":4}],"y":1}}},"outer":{"a":{"a":"é","m":[],"z":{"x":[{"c":3,"d":4}],"y":1}},"b":{"a":"é","m":[],"z":{"x":[{"c":3,"d":4}],"y":1}}}}"#
+    );
+}
+
+#[test]
+fn matches_canonicalizing_the_parsed_value() {
+    let text = r#"[{"b": 1e-7, "a": [true, {"😀": 2, "€": 1}]}, 9007199254740993]"#;
+    let value: serde_json::Value = serde_json::from_str(text).unwrap();
+    let expected = value_to_vec(&value).unwrap();
+
+    assert_eq!(to_vec(&raw(text)).unwrap(), expected);
+    let mut canonicalizer = Canonicalizer::new();
+    for _ in 0..2 {
+        let output = canonicalizer.canonicalize(&[raw(text)]).unwrap();
+        assert_eq!(output[1..output.len() - 1], expected[..]);
+    }
+}
+
+#[test]
+fn raw_values_are_validated_strictly() {
+    let payload = raw(r#"{"a": [{"b": 1, "b": 2}]}"#);
+    let envelope = Envelope {
+        kind: "order",
+        payload: &payload,
+        id: 7,
+    };
+    let error = to_vec(&envelope).unwrap_err();
+    assert!(matches!(&error, Error::DuplicateKey { path } if path == "/payload/a/0/b"));
+
+    let error = to_vec(&[raw(r#""\ud800""#)]).unwrap_err();
+    assert!(matches!(error, Error::Json(_)));
+}
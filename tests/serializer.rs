@@ -0,0 +1,260 @@
+use std::collections::{BTreeMap, HashMap};
+
+use serde::{ser::SerializeMap, ser::SerializeSeq, Serialize, Serializer};
+use serde_json::json;
+use test_case::test_case;
+
+use serde_json_canonicalizer::{pipe, to_string, JcsSerializer};
+
+#[derive(Serialize)]
+enum Variants {
+    Unit,
+    Newtype(u8),
+    Tuple(u8, bool),
+    Struct { b: u8, a: bool },
+}
+
+#[derive(Serialize)]
+struct Newtype(&'static str);
+
+// serialize through a helper that only knows about the serde::Serializer trait
+fn serialize_generic<S: Serializer>(serializer: S) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(None)?;
+    map.serialize_entry("z", &[3, 2, 1])?;
+    map.serialize_entry("a", &json!({"y": null, "x": 1.5}))?;
+    map.end()
+}
+
+#[test]
+fn multiple_values_into_inner() {
+    let mut serializer = JcsSerializer::new(Vec::new());
+    json!({"b": 1, "a": 2}).serialize(&mut serializer).unwrap();
+    json!([true, "x"]).serialize(&mut serializer).unwrap();
+    1e21.serialize(&mut serializer).unwrap();
+    assert_eq!(
+        r#"{"a":2,"b":1}[true,"x"]1e+21"#,
+        String::from_utf8(serializer.into_inner()).unwrap()
+    );
+}
+
+#[test]
+fn generic_serializer_function() {
+    let mut serializer = JcsSerializer::new(Vec::new());
+    serialize_generic(&mut serializer).unwrap();
+    assert_eq!(
+        br#"{"a":{"x":1.5,"y":null},"z":[3,2,1]}"#,
+        serializer.into_inner().as_slice()
+    );
+}
+
+#[test]
+fn manual_seq() {
+    let mut serializer = JcsSerializer::new(Vec::new());
+    let mut seq = (&mut serializer).serialize_seq(Some(3)).unwrap();
+    seq.serialize_element(&1).unwrap();
+    seq.serialize_element(&json!({"b": [], "a": {}})).unwrap();
+    seq.serialize_element("\u{1}\n").unwrap();
+    seq.end().unwrap();
+    assert_eq!(
+        br#"[1,{"a":{},"b":[]},"\u0001\n"]"#,
+        serializer.into_inner().as_slice()
+    );
+}
+
+#[test_case(Variants::Unit ; "unit variant")]
+#[test_case(Variants::Newtype(1) ; "newtype variant")]
+#[test_case(Variants::Tuple(1, true) ; "tuple variant")]
+#[test_case(Variants::Struct { b: 1, a: false } ; "struct variant")]
+fn same_shape_as_serde_json(value: Variants) {
+    let json = serde_json::to_string(&value).unwrap();
+    assert_eq!(pipe(&json).unwrap(), to_string(&value).unwrap());
+}
+
+struct Bytes;
+
+impl Serialize for Bytes {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_bytes(&[0, 255])
+    }
+}
+
+#[test]
+fn other_types() {
+    assert_eq!(r#""value""#, to_string(&Newtype("value")).unwrap());
+    assert_eq!("null", to_string(&Some(())).unwrap());
+    assert_eq!("\"\u{7f}\"", to_string(&'\u{7f}').unwrap());
+    assert_eq!("[0,255]", to_string(&Bytes).unwrap());
+    assert_eq!(
+        r#"{"10":"b","9":"a"}"#,
+        to_string(&HashMap::from([(10, "b"), (9, "a")])).unwrap()
+    );
+    assert_eq!(
+        r#"{"true":1}"#,
+        to_string(&HashMap::from([(true, 1)])).unwrap()
+    );
+}
+
+#[test]
+fn nested_non_finite_numbers() {
+    assert!(to_string(&vec![f64::NAN]).is_err());
+    assert!(to_string(&HashMap::from([("a", f32::INFINITY)])).is_err());
+}
+
+#[test]
+fn non_string_keys() {
+    assert!(to_string(&HashMap::from([((), 1)])).is_err());
+    assert!(to_string(&HashMap::from([(vec![1], 1)])).is_err());
+}
+
+#[test]
+fn escaped_keys_sort_by_unescaped_text() {
+    let map = HashMap::from([
+        ("b", 7),
+        ("\\", 6),
+        ("\"", 3),
+        ("\n", 2),
+        ("\u{1}", 1),
+        ("A", 5),
+        ("/", 4),
+    ]);
+
+    assert_eq!(
+        to_string(&map).unwrap(),
+        r#"{"\u0001":1,"\n":2,"\"":3,"/":4,"A":5,"\\":6,"b":7}"#
+    );
+}
+
+#[test]
+fn non_string_keys_sort_as_strings() {
+    assert_eq!(
+        to_string(&HashMap::from([(10, 'a'), (9, 'b'), (-1, 'c')])).unwrap(),
+        r#"{"-1":"c","10":"a","9":"b"}"#
+    );
+    assert_eq!(
+        to_string(&HashMap::from([(true, 1), (false, 2)])).unwrap(),
+        r#"{"false":2,"true":1}"#
+    );
+}
+
+#[test]
+fn objects_nested_in_arrays_and_objects() {
+    let value = json!({
+        "b": [{"d": 1, "c": [{"f": {}, "e": [{"h": 0, "g": 1}, {}]}, 2]}, "x"],
+        "a": {"z": {"y": {"x": {}}}, "w": [[{"v": 0, "u": 1}]]}
+    });
+    assert_eq!(
+        to_string(&value).unwrap(),
+        r#"{"a":{"w":[[{"u":1,"v":0}]],"z":{"y":{"x":{}}}},"b":[{"c":[{"e":[{"g":1,"h":0},{}],"f":{}},2],"d":1},"x"]}"#
+    );
+}
+
+#[test]
+fn consecutive_objects_reuse_buffers() {
+    let mut serializer = JcsSerializer::new(Vec::new());
+    for _ in 0..3 {
+        json!({"b": {"d": 1, "c": 2}, "a": [{"f": 3, "e": 4}]})
+            .serialize(&mut serializer)
+            .unwrap();
+    }
+    assert_eq!(
+        r#"{"a":[{"e":4,"f":3}],"b":{"c":2,"d":1}}"#.repeat(3),
+        String::from_utf8(serializer.into_inner()).unwrap()
+    );
+}
+
+#[test]
+fn duplicate_keys_keep_the_first_value() {
+    let mut serializer = JcsSerializer::new(Vec::new());
+    let mut map = (&mut serializer).serialize_map(None).unwrap();
+    map.serialize_entry("b", &1).unwrap();
+    map.serialize_entry("a", &json!({})).unwrap();
+    map.serialize_entry("b", &json!({"c": 2})).unwrap();
+    map.end().unwrap();
+    assert_eq!(br#"{"a":{},"b":1}"#, serializer.into_inner().as_slice());
+}
+
+#[test]
+fn empty_objects_at_the_end_of_values() {
+    let value = json!([{"a": {}}, {"b": {"c": {}}, "a": [{}]}, {}]);
+    assert_eq!(
+        to_string(&value).unwrap(),
+        r#"[{"a":{}},{"a":[{}],"b":{"c":{}}},{}]"#
+    );
+}
+
+#[test]
+fn large_object_with_escaped_keys() {
+    // larger than the chunks the formatter buffers objects in, with keys and values spanning them
+    let map: std::collections::BTreeMap<String, serde_json::Value> = (0..20_000)
+        .map(|index| {
+            let key = if index % 3 == 0 {
+                format!("\"key\"\n{index}")
+            } else {
+                format!("key{index}")
+            };
+            (
+                key,
+                json!({"value": "\u{1}".repeat(index % 7), "index": index}),
+            )
+        })
+        .collect();
+    let hash_map: HashMap<_, _> = map.clone().into_iter().collect();
+
+    // BTreeMap orders ASCII keys the same way as JCS, and serde_json escapes them the same way
+    assert_eq!(
+        to_string(&hash_map).unwrap(),
+        serde_json::to_string(&map).unwrap()
+    );
+}
+
+#[test]
+fn large_and_small_nested_objects() {
+    // nested objects both smaller and larger than the ones the formatter sorts as soon as they end
+    let inner = |size: usize| -> std::collections::BTreeMap<String, serde_json::Value> {
+        (0..size)
+            .map(|index| {
+                (
+                    format!("k{index}"),
+                    json!({"z": index, "a": [{"y": 1, "b": 2}]}),
+                )
+            })
+            .collect()
+    };
+    let map: std::collections::BTreeMap<String, _> = [10, 1_000, 3]
+        .into_iter()
+        .enumerate()
+        .map(|(index, size)| {
+            (
+                format!("o{index}"),
+                json!({"b": inner(size), "a": [inner(size)]}),
+            )
+        })
+        .collect();
+    let hash_map: HashMap<_, _> = map.clone().into_iter().collect();
+
+    assert_eq!(
+        to_string(&hash_map).unwrap(),
+        serde_json::to_string(&map).unwrap()
+    );
+}
+
+#[derive(Serialize)]
+enum Failing {
+    Tuple(u8, f64),
+    Struct { a: Vec<BTreeMap<&'static str, f64>> },
+}
+
+#[test_case(BTreeMap::from([("x", f64::NAN)]), "" ; "object")]
+#[test_case(vec![vec![1.0], vec![f64::NAN]], "[[1],[" ; "array")]
+#[test_case(vec![BTreeMap::from([("a", vec![f64::NAN])])], "[" ; "object in array")]
+#[test_case(Failing::Tuple(1, f64::INFINITY), "" ; "tuple variant")]
+#[test_case(Failing::Struct { a: vec![BTreeMap::from([("b", f64::NAN)])] }, "" ; "struct variant")]
+fn reusable_after_error<T: Serialize>(value: T, written: &str) {
+    let mut serializer = JcsSerializer::new(Vec::new());
+    value.serialize(&mut serializer).unwrap_err();
+    json!({"b": [2], "a": 1})
+        .serialize(&mut serializer)
+        .unwrap();
+    let output = String::from_utf8(serializer.into_inner()).unwrap();
+    assert_eq!(output, format!(r#"{written}{{"a":1,"b":[2]}}"#));
+}
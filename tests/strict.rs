@@ -0,0 +1,80 @@
+use serde::Deserialize;
+use serde_json::{json, Value};
+use test_case::test_case;
+
+use serde_json::error::Category;
+use serde_json_canonicalizer::{from_slice_strict, from_str_strict, pipe, pipe_strict, Error};
+
+#[test_case(r#"{"a":1,"a":2}"# => "duplicate object key at `/a`" ; "duplicate key")]
+#[test_case(r#"[0,{"x":[{},{"a~/b":1,"a~/b":2}]}]"# => "duplicate object key at `/1/x/1/a~0~1b`" ; "nested duplicate key")]
+#[test_case(r#"{"a":["\ud800"]}"# => "unexpected end of hex escape at line 1 column 14 at `/a/0`" ; "lone leading surrogate")]
+#[test_case(r#"{"a":{"b":"\udc00"}}"# => "lone leading surrogate in hex escape at line 1 column 17 at `/a/b`" ; "lone trailing surrogate")]
+#[test_case(r#"{"a":[1,-1e309]}"# => "number out of range at line 1 column 14 at `/a/1`" ; "number out of range")]
+#[test_case(r#"{"a":1} x"# => "trailing characters at line 1 column 9" ; "trailing characters")]
+fn strict_errors(json: &str) -> String {
+    from_str_strict::<Value>(json).unwrap_err().to_string()
+}
+
+#[test]
+fn strict_error_keeps_source() {
+    match from_str_strict::<Value>(r#"{"a":[1,-1e309]}"#).unwrap_err() {
+        Error::Strict { path, source } => {
+            assert_eq!(path, "/a/1");
+            assert_eq!(source.classify(), Category::Syntax);
+            assert_eq!((source.line(), source.column()), (1, 14));
+        }
+        error => panic!("unexpected error {error}"),
+    }
+
+    match from_str_strict::<Value>("{\"a\":\n[\"\\ud800\"]}").unwrap_err() {
+        Error::Strict { path, source } => {
+            assert_eq!(path, "/a/0");
+            assert_eq!(source.classify(), Category::Syntax);
+            assert_eq!((source.line(), source.column()), (2, 9));
+        }
+        error => panic!("unexpected error {error}"),
+    }
+
+    let error = from_str_strict::<Value>(r#"{"a":1} x"#).unwrap_err();
+    assert!(matches!(error, Error::Json(_)));
+    assert_eq!(error.path(), None);
+
+    let error = from_str_strict::<Value>(r#"{"a":{"b":1,"b":2}}"#).unwrap_err();
+    assert!(matches!(&error, Error::DuplicateKey { path } if path == "/a/b"));
+}
+
+#[test]
+fn duplicate_keys_are_accepted_by_pipe() {
+    let json = r#"{"a":1,"a":2}"#;
+    assert_eq!(r#"{"a":2}"#, pipe(json).unwrap());
+    assert!(pipe_strict(json).is_err());
+}
+
+#[test]
+fn strict_pipe() {
+    let json = r#"{ "b": [1.0, "A"], "a": {"y": null, "x": true} }"#;
+    assert_eq!(pipe(json).unwrap(), pipe_strict(json).unwrap());
+}
+
+#[test]
+fn typed() {
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Data {
+        a: u32,
+        b: Vec<String>,
+    }
+
+    let data: Data = from_slice_strict(br#"{"b":["x"],"a":1}"#).unwrap();
+    assert_eq!(
+        Data {
+            a: 1,
+            b: vec!["x".to_string()]
+        },
+        data
+    );
+    assert!(from_slice_strict::<Data>(br#"{"b":["x"],"a":1,"a":2}"#).is_err());
+    assert_eq!(
+        json!({"a": 1}),
+        from_slice_strict::<Value>(br#"{"a":1}"#).unwrap()
+    );
+}
@@ -0,0 +1,60 @@
+use proptest::prelude::*;
+use serde_json::json;
+
+use serde_json_canonicalizer::{
+    pipe_with, to_vec, to_vec_with, value_to_vec, value_to_vec_with, Error, IntegerPolicy,
+};
+
+use common::value;
+
+mod common;
+
+proptest! {
+    #[test]
+    fn same_bytes_as_to_vec(value in value()) {
+        prop_assert_eq!(value_to_vec(&value).unwrap(), to_vec(&value).unwrap());
+    }
+
+    #[test]
+    fn same_integer_policy_as_to_vec_with(value in value()) {
+        for policy in [IntegerPolicy::Lossy, IntegerPolicy::Error, IntegerPolicy::String] {
+            // of several lossy integers the paths may report different ones
+            prop_assert_eq!(
+                value_to_vec_with(&value, policy).ok(),
+                to_vec_with(&value, policy).ok()
+            );
+        }
+    }
+}
+
+#[test]
+fn integer_policy() {
+    let value = json!({"ids": [9007199254740991u64, 9007199254740993u64, -9007199254740993i64]});
+
+    let error = value_to_vec_with(&value, IntegerPolicy::Error).unwrap_err();
+    assert!(matches!(&error, Error::LossyInteger { path, value }
+        if path == "/ids/1" && value == "9007199254740993"));
+    assert_eq!(
+        value_to_vec_with(&value, IntegerPolicy::String).unwrap(),
+        br#"{"ids":[9007199254740991,"9007199254740993","-9007199254740993"]}"#
+    );
+    assert_eq!(
+        value_to_vec(&value).unwrap(),
+        br#"{"ids":[9007199254740991,9007199254740992,-9007199254740992]}"#
+    );
+    // floats are doubles already, nothing is lost
+    assert!(value_to_vec_with(&json!(9007199254740993.0), IntegerPolicy::Error).is_ok());
+
+    assert!(pipe_with(r#"[9007199254740993]"#, IntegerPolicy::Error).is_err());
+}
+
+#[test]
+fn keys_sort_by_utf16_code_units() {
+    // U+10000 is a surrogate pair in UTF-16, which sorts before U+E000
+    let value = json!({"\u{e000}": 1, "\u{10000}": 2, "\u{7f}": 3, "": 4});
+
+    assert_eq!(
+        value_to_vec(&value).unwrap(),
+        "{\"\":4,\"\u{7f}\":3,\"\u{10000}\":2,\"\u{e000}\":1}".as_bytes()
+    );
+}
@@ -0,0 +1,71 @@
+use std::fs::read_dir;
+
+use test_case::test_case;
+
+use serde_json_canonicalizer::{is_canonical, to_vec, verify_canonical, Deviation};
+
+#[test_case(b"null" ; "null")]
+#[test_case(b"[true,false]" ; "literals")]
+#[test_case(b"{}" ; "empty object")]
+#[test_case(b"[]" ; "empty array")]
+#[test_case(b"[0,-1,1e+21,1e-7,0.000001,333333333.3333333]" ; "numbers")]
+#[test_case(br#""\u001f\b\t\n\f\r\"\\/""# ; "escapes")]
+#[test_case(r#"{"":0,"\r":1,"a":{"a":[],"b":{}},"ö":2,"😂":3,"דּ":4}"#.as_bytes() ; "sorted keys")]
+fn canonical(json: &[u8]) {
+    assert_eq!(Ok(()), verify_canonical(json));
+}
+
+#[test_case(b"{\"a\": 1}" => (5, Deviation::Whitespace) ; "whitespace after colon")]
+#[test_case(b" 1" => (0, Deviation::Whitespace) ; "leading whitespace")]
+#[test_case(b"[1]\n" => (3, Deviation::Whitespace) ; "trailing newline")]
+#[test_case(b"[1 ,2]" => (2, Deviation::Whitespace) ; "whitespace in array")]
+#[test_case(br#"{"b":1,"a":2}"# => (7, Deviation::UnsortedKey) ; "unsorted keys")]
+#[test_case("{\"\u{fb33}\":1,\"\u{1f602}\":2}".as_bytes() => (9, Deviation::UnsortedKey) ; "utf8 instead of utf16 order")]
+#[test_case(br#"{"a":{"b":1,"b":2}}"# => (12, Deviation::DuplicateKey) ; "duplicate keys")]
+#[test_case(b"1.0" => (0, Deviation::NonCanonicalNumber) ; "trailing zero")]
+#[test_case(b"[1,1E2]" => (3, Deviation::NonCanonicalNumber) ; "exponent")]
+#[test_case(b"-0" => (0, Deviation::NonCanonicalNumber) ; "negative zero")]
+#[test_case(b"1e400" => (0, Deviation::NonCanonicalNumber) ; "out of range")]
+#[test_case(b"9007199254740993" => (0, Deviation::NonCanonicalNumber) ; "not representable")]
+#[test_case(br#""a\/""# => (2, Deviation::NonCanonicalEscape) ; "escaped solidus")]
+#[test_case(br#""\u0041""# => (1, Deviation::NonCanonicalEscape) ; "escaped letter")]
+#[test_case(br#""\u000a""# => (1, Deviation::NonCanonicalEscape) ; "long form escape")]
+#[test_case(br#""\u001F""# => (1, Deviation::NonCanonicalEscape) ; "uppercase hex")]
+#[test_case(br#"{"\u00e9":1}"# => (2, Deviation::NonCanonicalEscape) ; "escaped key")]
+#[test_case(br#""\ud800""# => (1, Deviation::InvalidJson) ; "lone surrogate")]
+#[test_case(b"" => (0, Deviation::InvalidJson) ; "empty")]
+#[test_case(b"[1]]" => (3, Deviation::InvalidJson) ; "trailing characters")]
+#[test_case(b"[tru]" => (1, Deviation::InvalidJson) ; "invalid literal")]
+#[test_case(b"[01]" => (2, Deviation::InvalidJson) ; "leading zero")]
+#[test_case(b"\"\xff\"" => (1, Deviation::InvalidJson) ; "invalid utf8")]
+#[test_case(b"\"\t\"" => (1, Deviation::InvalidJson) ; "unescaped control character")]
+fn not_canonical(json: &[u8]) -> (usize, Deviation) {
+    let error = verify_canonical(json).unwrap_err();
+    (error.offset(), error.deviation())
+}
+
+#[test]
+fn recursion_limit() {
+    let json = format!("{}{}", "[".repeat(129), "]".repeat(129));
+    assert!(!is_canonical(json.as_bytes()));
+    let json = format!("{}{}", "[".repeat(128), "]".repeat(128));
+    assert!(is_canonical(json.as_bytes()));
+}
+
+// the expected outputs of the reference implementation are canonical,
+// and every output of the serializer passes the verification
+#[test]
+fn reference_testdata() {
+    for file in read_dir("tests/resources/testdata/input").unwrap() {
+        let path = file.unwrap().path();
+        let input = std::fs::read(&path).unwrap();
+        let expected = std::fs::read(
+            std::path::Path::new("tests/resources/testdata/output").join(path.file_name().unwrap()),
+        )
+        .unwrap();
+        assert_eq!(Ok(()), verify_canonical(&expected), "{:?}", path);
+        let value: serde_json::Value = serde_json::from_slice(&input).unwrap();
+        assert!(is_canonical(&to_vec(&value).unwrap()), "{:?}", path);
+        assert!(!is_canonical(&input), "{:?}", path);
+    }
+}